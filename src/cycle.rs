@@ -0,0 +1,165 @@
+//! Cycle detection for simulations that eventually repeat a state.
+//!
+//! A simulation is described by its start state and a `step` function computing the next state.
+//! If it repeats, the states look like `x0, x1, ..., x(prefix - 1)` followed by `period` states that
+//! then repeat forever.
+
+use std::hash::Hash;
+
+use rustc_hash::FxHashMap;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Cycle {
+    /// Number of states before the first one that's part of the cycle
+    pub prefix: usize,
+    /// Number of states in the cycle
+    pub period: usize,
+}
+
+impl Cycle {
+    /// Returns the earliest step that has the same state as step `n`
+    pub fn reduce(&self, n: usize) -> usize {
+        if n < self.prefix {
+            n
+        } else {
+            self.prefix + (n - self.prefix) % self.period
+        }
+    }
+}
+
+/// Floyd's "tortoise and hare". Only ever keeps a couple of states around, but needs to compute
+/// about three times as many steps as [`detect`].
+#[allow(dead_code, reason = "day 14 uses `nth_state` instead")]
+pub fn floyd<S: Clone + PartialEq>(start: S, mut step: impl FnMut(&S) -> S) -> Cycle {
+    let mut tortoise = step(&start);
+    let mut hare = step(&tortoise);
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        let next = step(&hare);
+        hare = step(&next);
+    }
+
+    // The distance between them is now a multiple of the period. Moving them at the same speed with
+    // that distance between them, they first meet at the start of the cycle.
+    let mut prefix = 0;
+    tortoise = start;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        prefix += 1;
+    }
+
+    let mut period = 1;
+    hare = step(&tortoise);
+    while tortoise != hare {
+        hare = step(&hare);
+        period += 1;
+    }
+
+    Cycle { prefix, period }
+}
+
+/// Brent's algorithm. Like [`floyd`], but needs fewer steps.
+#[allow(dead_code, reason = "day 14 uses `nth_state` instead")]
+pub fn brent<S: Clone + PartialEq>(start: S, mut step: impl FnMut(&S) -> S) -> Cycle {
+    // Find the period first: the hare moves ahead, and the tortoise teleports to it
+    // every time the distance reaches the next power of two
+    let mut power = 1;
+    let mut period = 1;
+    let mut tortoise = start.clone();
+    let mut hare = step(&start);
+    while tortoise != hare {
+        if power == period {
+            tortoise = hare.clone();
+            power *= 2;
+            period = 0;
+        }
+        hare = step(&hare);
+        period += 1;
+    }
+
+    // Then, with the hare `period` steps ahead, they meet at the start of the cycle
+    let mut prefix = 0;
+    tortoise = start.clone();
+    hare = start;
+    for _ in 0..period {
+        hare = step(&hare);
+    }
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        prefix += 1;
+    }
+
+    Cycle { prefix, period }
+}
+
+/// Remembers every state until one repeats. This computes the fewest steps, and also returns all of
+/// the states up to the end of the first cycle (so `states[cycle.reduce(n)]` is the state at step `n`).
+pub fn detect<S: Clone + Eq + Hash>(start: S, mut step: impl FnMut(&S) -> S) -> (Cycle, Vec<S>) {
+    let mut seen = FxHashMap::default();
+    let mut states = Vec::new();
+    let mut state = start;
+
+    loop {
+        if let Some(&prefix) = seen.get(&state) {
+            let period = states.len() - prefix;
+            return (Cycle { prefix, period }, states);
+        }
+
+        seen.insert(state.clone(), states.len());
+        let next = step(&state);
+        states.push(state);
+        state = next;
+    }
+}
+
+/// Returns the state after `n` steps, without actually having to compute all of them
+pub fn nth_state<S: Clone + Eq + Hash>(start: S, step: impl FnMut(&S) -> S, n: usize) -> S {
+    let (cycle, mut states) = detect(start, step);
+    states.swap_remove(cycle.reduce(n))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cycle() {
+        // 3, 10, 46, 27, 15, 6, 37, 50, 26, 17, 15, ...
+        let step = |&x: &u64| (x * x + 1) % 55;
+        let expected = Cycle {
+            prefix: 4,
+            period: 6,
+        };
+        assert_eq!(floyd(3, step), expected);
+        assert_eq!(brent(3, step), expected);
+        assert_eq!(detect(3, step).0, expected);
+        assert_eq!(detect(3, step).1, [3, 10, 46, 27, 15, 6, 37, 50, 26, 17]);
+
+        let brute = |n| (0..n).fold(3, |x, _| step(&x));
+        for n in 0..30 {
+            assert_eq!(nth_state(3, step, n), brute(n));
+        }
+        assert_eq!(
+            nth_state(3, step, 1_000_000_000_000),
+            brute(expected.reduce(1_000_000_000_000))
+        );
+
+        // Cycles can start right away, and have a period of one
+        assert_eq!(
+            brent(0, |&x: &u64| (x + 1) % 7),
+            Cycle {
+                prefix: 0,
+                period: 7
+            }
+        );
+        assert_eq!(
+            floyd(5, |&x: &u64| x.min(10)),
+            Cycle {
+                prefix: 0,
+                period: 1
+            }
+        );
+    }
+}
@@ -1,190 +1,328 @@
-use std::cmp::Ordering;
-use std::collections::VecDeque;
+use std::fmt::Write;
 
-use rustc_hash::FxHashSet;
+use itertools::Itertools;
+use owo_colors::OwoColorize;
 
+use crate::geometry;
+use crate::geometry::Point;
 use crate::grid::ByteGridView;
+use crate::grid::Direction;
+use crate::grid::Grid;
 use crate::grid::Position;
 
-#[derive(Copy, Clone)]
-enum Direction {
-    Top,
-    Bottom,
-    Left,
-    Right,
+/// Returns the directions in which the pipe on a tile connects to its neighbors
+fn connections(tile: u8) -> &'static [Direction] {
+    match tile {
+        b'|' => &[Direction::Up, Direction::Down],
+        b'-' => &[Direction::Left, Direction::Right],
+        b'L' => &[Direction::Up, Direction::Right],
+        b'J' => &[Direction::Up, Direction::Left],
+        b'7' => &[Direction::Down, Direction::Left],
+        b'F' => &[Direction::Down, Direction::Right],
+        _ => &[],
+    }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
-enum Edge {
-    Top,
-    Bottom,
-    Left,
-    Right,
+/// The loop of pipes that the animal on `S` is in
+struct PipeLoop {
+    /// The tiles of the loop in order, starting at `S`
+    tiles: Vec<Position>,
+    /// The pipe that `S` is hiding
+    start_pipe: u8,
 }
 
-impl Edge {
-    const ALL: &'static [Self] = &[Self::Top, Self::Bottom, Self::Left, Self::Right];
-
-    fn of(b: u8) -> &'static [Self] {
-        match b {
-            b'|' => &[Self::Top, Self::Bottom],
-            b'-' => &[Self::Left, Self::Right],
-            b'L' => &[Self::Top, Self::Right],
-            b'J' => &[Self::Top, Self::Left],
-            b'7' => &[Self::Left, Self::Bottom],
-            b'F' => &[Self::Bottom, Self::Right],
-            b'.' => &[],
-            b'S' => Self::ALL,
-            other => panic!("no edge for {}", other as char),
+impl PipeLoop {
+    fn find(grid: ByteGridView<'_>) -> Self {
+        let start = (0..grid.rows())
+            .flat_map(|y| (0..grid.columns()).map(move |x| Position { y, x }))
+            .find(|pos| grid[pos.y][pos.x] == b'S')
+            .expect("no start tile");
+
+        // The pipe on the start tile connects to exactly the neighbors that connect back to it.
+        // Any other pipes around it aren't part of the loop.
+        let exits = Direction::ALL
+            .into_iter()
+            .filter(|&dir| {
+                start
+                    .checked_offset(dir)
+                    .filter(|&next| grid.in_bounds(next))
+                    .is_some_and(|next| connections(grid[next.y][next.x]).contains(&dir.reverse()))
+            })
+            .collect_vec();
+        assert_eq!(
+            exits.len(),
+            2,
+            "start tile doesn't connect to exactly two pipes"
+        );
+        let start_pipe = b"|-LJ7F"
+            .iter()
+            .copied()
+            .find(|&pipe| connections(pipe).iter().all(|dir| exits.contains(dir)))
+            .unwrap();
+
+        let mut tiles = vec![start];
+        let mut dir = exits[0];
+        let mut pos = start.checked_offset(dir).unwrap();
+        while pos != start {
+            tiles.push(pos);
+            let pipe = grid[pos.y][pos.x];
+            assert!(
+                connections(pipe).contains(&dir.reverse()),
+                "{} at {pos:?} doesn't connect back to the loop",
+                pipe as char
+            );
+            // Keep following the pipe, i.e. leave through the end that we didn't come in through
+            dir = connections(pipe)
+                .iter()
+                .copied()
+                .find(|&next| next != dir.reverse())
+                .unwrap();
+            pos = pos
+                .checked_offset(dir)
+                .filter(|&next| grid.in_bounds(next))
+                .expect("loop leaves the grid");
         }
-    }
 
-    fn connects_to(direction: Direction, this: &[Self], other: &[Self]) -> bool {
-        this.iter().any(|v| match (v, direction) {
-            (Edge::Bottom, Direction::Top) => other.contains(&Edge::Top),
-            (Edge::Top, Direction::Bottom) => other.contains(&Edge::Bottom),
-            (Edge::Right, Direction::Left) => other.contains(&Edge::Left),
-            (Edge::Left, Direction::Right) => other.contains(&Edge::Right),
-            _ => false,
-        })
+        Self { tiles, start_pipe }
     }
-}
 
-fn extend_neighbor_edges(
-    pos: Position,
-    grid: ByteGridView,
-    length: usize,
-    edges: &mut VecDeque<(Position, usize)>,
-    seen: &mut FxHashSet<Position>,
-) -> bool {
-    let sym = grid[pos.y][pos.x];
-
-    let neighbors = grid
-        .non_diagonal_neighbors(pos.y, pos.x)
-        .filter(|&(v, ..)| v != b'.')
-        .filter(|&(_, y, x)| !seen.contains(&Position { y, x }));
-
-    let mut had_neighbor = false;
-    for (dir, dy, dx) in neighbors {
-        let dest = Position { y: dy, x: dx };
-
-        had_neighbor = true;
-
-        let direction = match (dy.cmp(&pos.y), dx.cmp(&pos.x)) {
-            (Ordering::Less, _) => Direction::Bottom,
-            (Ordering::Greater, _) => Direction::Top,
-            (_, Ordering::Less) => Direction::Right,
-            (_, Ordering::Greater) => Direction::Left,
-            _ => unreachable!(),
-        };
-        let this = Edge::of(sym);
-        let other = Edge::of(dir);
-
-        if Edge::connects_to(direction, this, other) {
-            edges.push_back((dest, length + 1));
+    /// Returns the pipe at `pos`, seeing through `S`
+    fn pipe_at(&self, grid: ByteGridView<'_>, pos: Position) -> u8 {
+        if pos == self.tiles[0] {
+            self.start_pipe
+        } else {
+            grid[pos.y][pos.x]
         }
     }
-    had_neighbor
 }
 
 pub fn part1(input: &str) -> i64 {
-    let grid = ByteGridView::from(input);
-    let start = input.bytes().position(|v| v == b'S').unwrap();
-    let sx = grid.norm_to_col(start);
-    let sy = grid.norm_to_row(start);
-
-    let mut seen = FxHashSet::default();
-    let mut queue = VecDeque::<(Position, usize)>::new();
-    queue.push_back((Position { x: sx, y: sy }, 0));
-
-    while let Some((pos, length)) = queue.pop_front() {
-        seen.insert(pos);
+    // Going both ways around the loop, the farthest tile is halfway around
+    (PipeLoop::find(ByteGridView::from(input)).tiles.len() / 2) as i64
+}
 
-        if !extend_neighbor_edges(pos, grid, length, &mut queue, &mut seen) {
-            return length as i64;
-        }
-    }
+pub fn part2(input: &str) -> i64 {
+    let pipe_loop = PipeLoop::find(ByteGridView::from(input));
+    let vertices = pipe_loop
+        .tiles
+        .iter()
+        .map(|&pos| Point::from(pos))
+        .collect_vec();
 
-    panic!("reached bfs end without finding a loop?")
+    // Every tile is a point with integer coordinates, so Pick's theorem gives the number of
+    // points (tiles) strictly inside of the loop
+    geometry::interior_points(&vertices)
 }
 
-pub fn part2(input: &str) -> i64 {
-    let grid = ByteGridView::from(input);
-    let start = input.bytes().position(|v| v == b'S').unwrap();
-    let sx = grid.norm_to_col(start);
-    let sy = grid.norm_to_row(start);
-
-    let mut seen = FxHashSet::default();
-    let mut queue = VecDeque::<(Position, usize)>::new();
-    queue.push_back((Position { x: sx, y: sy }, 0));
-
-    let mut min_x = usize::MAX;
-    let mut min_y = usize::MAX;
-    let mut max_x = usize::MIN;
-    let mut max_y = usize::MIN;
-
-    while let Some((pos, length)) = queue.pop_front() {
-        min_x = min_x.min(pos.x);
-        min_y = min_y.min(pos.y);
-        max_x = max_x.max(pos.x);
-        max_y = max_y.max(pos.y);
-
-        seen.insert(pos);
-
-        if !extend_neighbor_edges(pos, grid, length, &mut queue, &mut seen) {
-            break;
-        }
+/// Returns whether every tile is enclosed by the loop, in row-major order. Tiles of the loop
+/// itself are never inside.
+fn inside_tiles(grid: ByteGridView<'_>, pipe_loop: &PipeLoop) -> Vec<bool> {
+    let mut on_loop = vec![false; grid.rows() * grid.columns()];
+    for pos in &pipe_loop.tiles {
+        on_loop[pos.y * grid.columns() + pos.x] = true;
     }
 
-    // let mut depth = 0;
-    let mut within_pipe = false;
-    let mut points = 0;
-
+    let mut inside = vec![false; grid.rows() * grid.columns()];
     // Algorithm:
     // https://en.wikipedia.org/wiki/Point_in_polygon
-    // (using a bool instead of counter % 2 == 1)
-    for y in min_y..=max_y {
-        let mut last_pipe = None;
-
-        for x in min_x..=max_x {
-            let pos = Position { x, y };
-            let c = grid[y][x];
-
-            if seen.contains(&pos) {
-                if (c == b'7' && last_pipe == Some(b'F')) || (c == b'J' && last_pipe == Some(b'L'))
-                {
-                    within_pipe = !within_pipe;
-                } else if c != b'-'
-                    && !(last_pipe == Some(b'L') && c == b'7')
-                    && !(last_pipe == Some(b'F') && c == b'J')
-                {
-                    last_pipe = Some(c);
+    // Casting a ray along the top edge of every tile, only pipes going up cross it.
+    for y in 0..grid.rows() {
+        let mut within_pipe = false;
+        for x in 0..grid.columns() {
+            let index = y * grid.columns() + x;
+            if on_loop[index] {
+                let pipe = pipe_loop.pipe_at(grid, Position { y, x });
+                if connections(pipe).contains(&Direction::Up) {
                     within_pipe = !within_pipe;
                 }
-            } else if within_pipe {
-                points += 1;
+            } else {
+                inside[index] = within_pipe;
             }
         }
     }
 
-    points
+    inside
+}
+
+/// Draws the maze with the loop in box-drawing characters and every other tile as `I` if the loop
+/// encloses it. Outside of the loop, pipes are blanked out and the ground is drawn as `O`.
+/// With `color`, the loop is highlighted using ANSI escape codes.
+pub fn render(grid: ByteGridView<'_>, color: bool) -> String {
+    let pipe_loop = PipeLoop::find(grid);
+    let inside = inside_tiles(grid, &pipe_loop);
+    let mut on_loop = vec![false; grid.rows() * grid.columns()];
+    for pos in &pipe_loop.tiles {
+        on_loop[pos.y * grid.columns() + pos.x] = true;
+    }
+
+    let mut out = String::new();
+    for y in 0..grid.rows() {
+        for x in 0..grid.columns() {
+            let index = y * grid.columns() + x;
+            if on_loop[index] {
+                let glyph = match pipe_loop.pipe_at(grid, Position { y, x }) {
+                    b'|' => '│',
+                    b'-' => '─',
+                    b'L' => '└',
+                    b'J' => '┘',
+                    b'7' => '┐',
+                    b'F' => '┌',
+                    other => unreachable!("{} is not a pipe", other as char),
+                };
+                if color {
+                    write!(out, "{}", glyph.yellow().bold()).unwrap();
+                } else {
+                    out.push(glyph);
+                }
+            } else if inside[index] {
+                if color {
+                    write!(out, "{}", 'I'.green()).unwrap();
+                } else {
+                    out.push('I');
+                }
+            } else if !connections(grid[y][x]).is_empty() {
+                out.push(' ');
+            } else if color {
+                write!(out, "{}", 'O'.bright_black()).unwrap();
+            } else {
+                out.push('O');
+            }
+        }
+        out.push('\n');
+    }
+    out
 }
 
 #[cfg(test)]
 #[test]
 fn p10t() {
-    assert!(Edge::connects_to(
-        Direction::Bottom,
-        Edge::of(b'J'),
-        Edge::of(b'F')
-    ));
-    assert!(!Edge::connects_to(
-        Direction::Top,
-        Edge::of(b'J'),
-        Edge::of(b'F')
-    ));
+    use crate::flood;
+    use crate::grid::ByteGrid;
 
     const INPUT: &str = include_str!("../inputs/day10.txt");
+    // The pipes around the loop that don't connect to it don't matter
+    let example = "-L|F7
+7S-7|
+L|7||
+-L-J|
+L|-JF";
+    let complex = "7-F7-
+.FJ|7
+SJLL7
+|F--J
+LJ.LJ";
+    assert_eq!(part1(example), 4);
+    assert_eq!(part1(complex), 8);
+    assert_eq!(PipeLoop::find(ByteGridView::from(example)).start_pipe, b'F');
+    let pipe_loop = PipeLoop::find(ByteGridView::from(complex));
+    assert_eq!(pipe_loop.start_pipe, b'F');
+    assert_eq!(pipe_loop.tiles.len(), 16);
+    assert_eq!(
+        pipe_loop.tiles[..3],
+        [
+            Position { y: 2, x: 0 },
+            Position { y: 2, x: 1 },
+            Position { y: 1, x: 1 }
+        ]
+    );
+
+    let enclosed = "...........
+.S-------7.
+.|F-----7|.
+.||.....||.
+.||.....||.
+.|L-7.F-J|.
+.|..|.|..|.
+.L--J.L--J.
+...........";
+    let squeezed = "..........
+.S------7.
+.|F----7|.
+.||....||.
+.||....||.
+.|L-7F-J|.
+.|..||..|.
+.L--JL--J.
+..........";
+    let larger = ".F----7F7F7F7F-7....
+.|F--7||||||||FJ....
+.||.FJ||||||||L7....
+FJL7L7LJLJ||LJ.L-7..
+L--J.L7...LJS7F-7L7.
+....F-J..F7FJ|L7L7L7
+....L7.F7||L7|.L7L7|
+.....|FJLJ|FJ|F7|.LJ
+....FJL-7.||.||||...
+....L---J.LJ.LJLJ...";
+    let junk = "FF7FSF7F7F7F7F7F---7
+L|LJ||||||||||||F--J
+FL-7LJLJ||||||LJL-77
+F--JF--7||LJLJ7F7FJ-
+L---JF-JLJ.||-FJLJJ7
+|F|F-JF---7F7-L7L|7|
+|FFJF7L7F-JF7|JL---7
+7-L-JL7||F7|L7F-7F7|
+L.L7LFJ|||||FJL7||LJ
+L7JLJL-JLJLJL--JLJ.L";
+
+    for (input, expected) in [
+        (example, 1),
+        (complex, 1),
+        (enclosed, 4),
+        (squeezed, 4),
+        (larger, 8),
+        (junk, 10),
+    ] {
+        assert_eq!(part2(input), expected);
+
+        let grid = ByteGridView::from(input);
+        let pipe_loop = PipeLoop::find(grid);
+        let inside = inside_tiles(grid, &pipe_loop);
+        assert_eq!(
+            inside.iter().filter(|&&inside| inside).count(),
+            expected as usize
+        );
+        assert_eq!(render(grid, false).matches('I').count(), expected as usize);
+
+        // Scaled up 3x, every tile becomes a 3x3 block with the pipe going through the middle,
+        // so the flood fill can squeeze between pipes
+        let mut scaled = ByteGrid::new(grid.rows() * 3, grid.columns() * 3, b'.');
+        for &pos in &pipe_loop.tiles {
+            let (y, x) = (pos.y * 3 + 1, pos.x * 3 + 1);
+            scaled[y][x] = b'#';
+            for &dir in connections(pipe_loop.pipe_at(grid, pos)) {
+                let end = Position { y, x }.checked_offset(dir).unwrap();
+                scaled[end.y][end.x] = b'#';
+            }
+        }
+        let outside = flood::flood_fill(&scaled, Position { y: 0, x: 0 }, |_, c| c == b'.');
+        for y in 0..grid.rows() {
+            for x in 0..grid.columns() {
+                let center = Position {
+                    y: y * 3 + 1,
+                    x: x * 3 + 1,
+                };
+                let enclosed = scaled[center.y][center.x] == b'.' && !outside.contains(&center);
+                assert_eq!(enclosed, inside[y * grid.columns() + x]);
+            }
+        }
+    }
+
+    // Blanked out pipes leave trailing spaces, which aren't worth keeping in here
+    let render_lines = |input: &str| {
+        render(ByteGridView::from(input), false)
+            .lines()
+            .map(|line| line.trim_end().to_owned())
+            .collect_vec()
+    };
+    assert_eq!(render_lines(example), ["", " ┌─┐", " │I│", " └─┘", ""]);
+    assert!(render(ByteGridView::from(example), true).contains("\x1b["));
+    // Only the pipes outside of the loop are blanked out
+    assert_eq!(
+        render_lines(complex),
+        ["  ┌┐", "O┌┘│", "┌┘I└┐", "│┌──┘", "└┘O"]
+    );
+
     assert_eq!(part1(INPUT.trim()), 7086);
     assert_eq!(part2(INPUT.trim()), 317);
 }
@@ -1,77 +1,241 @@
-use std::cmp::Ordering;
+use itertools::Itertools;
 
 use crate::grid::ByteGridView;
 use crate::grid::Position;
 
-fn normalize_coords(
-    cols: &[bool],
-    rows: &[bool],
-    x: usize,
-    y: usize,
-    expansions: usize,
-) -> (usize, usize) {
-    let mut nx = x;
-    let mut ny = y;
-    for _ in cols.iter().take(x).filter(|&&v| !v) {
-        nx += expansions - 1;
-    }
-    for _ in rows.iter().take(y).filter(|&&v| !v) {
-        ny += expansions - 1;
-    }
-    (nx, ny)
+/// For every index, how far it moves when every empty line (row or column) before it grows to
+/// `expansion_factor` lines
+fn expansion_offsets(occupied: &[bool], expansion_factor: usize) -> Vec<usize> {
+    occupied
+        .iter()
+        .scan(0, |offset, &occupied| {
+            let current = *offset;
+            if !occupied {
+                *offset += expansion_factor - 1;
+            }
+            Some(current)
+        })
+        .collect()
 }
 
-// Part 1 and part 2 are literally the same except for the amount of expansions per empty row/column
-fn solve(input: &str, expansions: usize) -> i64 {
-    let grid = ByteGridView::from(input);
-    let mut galaxy_rows = vec![false; grid.rows()];
-    let mut galaxy_cols = vec![false; grid.columns()];
-    let mut galaxies = Vec::new();
-
-    for y in 0..grid.rows() {
-        for (x, &col) in grid[y].iter().enumerate() {
-            let is_galaxy = col == b'#';
-            galaxy_rows[y] |= is_galaxy;
-            galaxy_cols[x] |= is_galaxy;
-            if is_galaxy {
-                galaxies.push(Position { x, y });
+/// The universe after expanding every empty row and column to `expansion_factor` of them
+pub struct Universe {
+    /// The positions of all galaxies in the expanded universe, in row-major order
+    galaxies: Vec<Position>,
+    #[allow(dead_code, reason = "only needed by `render`")]
+    rows: usize,
+    #[allow(dead_code, reason = "only needed by `render`")]
+    columns: usize,
+}
+
+impl Universe {
+    pub fn new(grid: ByteGridView<'_>, expansion_factor: usize) -> Self {
+        assert!(
+            expansion_factor >= 1,
+            "empty lines can't shrink to less than one line"
+        );
+        let mut galaxy_rows = vec![false; grid.rows()];
+        let mut galaxy_cols = vec![false; grid.columns()];
+        let mut galaxies = Vec::new();
+
+        for y in 0..grid.rows() {
+            for (x, &col) in grid[y].iter().enumerate() {
+                let is_galaxy = col == b'#';
+                galaxy_rows[y] |= is_galaxy;
+                galaxy_cols[x] |= is_galaxy;
+                if is_galaxy {
+                    galaxies.push(Position { x, y });
+                }
             }
         }
+
+        let row_offsets = expansion_offsets(&galaxy_rows, expansion_factor);
+        let col_offsets = expansion_offsets(&galaxy_cols, expansion_factor);
+        for galaxy in &mut galaxies {
+            galaxy.y += row_offsets[galaxy.y];
+            galaxy.x += col_offsets[galaxy.x];
+        }
+
+        let empty = |occupied: &[bool]| occupied.iter().filter(|&&occupied| !occupied).count();
+        Self {
+            galaxies,
+            rows: grid.rows() + empty(&galaxy_rows) * (expansion_factor - 1),
+            columns: grid.columns() + empty(&galaxy_cols) * (expansion_factor - 1),
+        }
+    }
+
+    /// Returns the expanded positions of all galaxies, in row-major order.
+    /// Galaxies are referred to by their index in here.
+    #[allow(dead_code, reason = "only used by tests")]
+    pub fn galaxies(&self) -> &[Position] {
+        &self.galaxies
+    }
+
+    /// Returns the length of the shortest path between the galaxies `a` and `b`
+    #[allow(dead_code, reason = "the total is summed up per axis instead")]
+    pub fn distance(&self, a: usize, b: usize) -> usize {
+        let (a, b) = (self.galaxies[a], self.galaxies[b]);
+        a.y.abs_diff(b.y) + a.x.abs_diff(b.x)
     }
 
-    let mut steps = 0;
+    /// Returns the sum of the shortest paths between all pairs of galaxies
+    pub fn total_distance(&self) -> usize {
+        // The Manhattan distance is the sum of the distances on both axes, which can be summed up
+        // separately
+        let ys = self.galaxies.iter().map(|galaxy| galaxy.y).collect();
+        let xs = self.galaxies.iter().map(|galaxy| galaxy.x).collect();
+        axis_distance_sum(ys) + axis_distance_sum(xs)
+    }
 
-    for (i, galaxy) in galaxies.iter().copied().enumerate() {
-        let (tx, ty) = normalize_coords(&galaxy_cols, &galaxy_rows, galaxy.x, galaxy.y, expansions);
+    /// Returns the pair of galaxies closest to each other (the first one in order if there are several),
+    /// or `None` if there are fewer than two galaxies.
+    #[allow(dead_code, reason = "neither part asks for a single pair")]
+    pub fn closest_pair(&self) -> Option<(usize, usize)> {
+        (0..self.galaxies.len())
+            .tuple_combinations()
+            .min_by_key(|&(a, b)| self.distance(a, b))
+    }
 
-        for &galaxy in &galaxies[i..] {
-            let (ox, oy) =
-                normalize_coords(&galaxy_cols, &galaxy_rows, galaxy.x, galaxy.y, expansions);
+    /// Returns a pair of galaxies farthest from each other, or `None` if there are fewer than two
+    /// galaxies.
+    #[allow(dead_code, reason = "neither part asks for a single pair")]
+    pub fn farthest_pair(&self) -> Option<(usize, usize)> {
+        if self.galaxies.len() < 2 {
+            return None;
+        }
 
-            let xdist = tx.abs_diff(ox);
-            let ydist = ty.abs_diff(oy);
-            steps += match xdist.cmp(&ydist) {
-                Ordering::Greater | Ordering::Equal => (ydist * 2) + xdist - ydist,
-                Ordering::Less => (xdist * 2) + ydist - xdist,
-            };
+        // Rotated by 45 degrees, the Manhattan distance is the larger of the distances on the two
+        // diagonal axes, which is largest between the galaxies at either end of one of them
+        let diagonals: [fn(Position) -> isize; 2] = [
+            |pos| (pos.y + pos.x) as isize,
+            |pos| pos.y as isize - pos.x as isize,
+        ];
+        diagonals
+            .into_iter()
+            .map(|diagonal| {
+                let (min, max) = (0..self.galaxies.len())
+                    .minmax_by_key(|&i| diagonal(self.galaxies[i]))
+                    .into_option()
+                    .unwrap();
+                (min.min(max), min.max(max))
+            })
+            .max_by_key(|&(a, b)| self.distance(a, b))
+    }
+
+    /// Draws the expanded universe, with galaxies as `#` and empty space as `.`.
+    /// This is only feasible for small expansion factors.
+    #[allow(dead_code, reason = "only meant for debugging")]
+    pub fn render(&self) -> String {
+        let mut out = vec![b'.'; self.rows * (self.columns + 1)];
+        for y in 0..self.rows {
+            out[y * (self.columns + 1) + self.columns] = b'\n';
+        }
+        for galaxy in &self.galaxies {
+            out[galaxy.y * (self.columns + 1) + galaxy.x] = b'#';
         }
+        String::from_utf8(out).unwrap()
+    }
+}
+
+/// Returns the sum of the distances between all pairs of coordinates along one axis
+fn axis_distance_sum(mut coords: Vec<usize>) -> usize {
+    coords.sort_unstable();
+
+    // Once sorted, every coordinate is at least as far as all of the ones before it, so its distance to
+    // each of them is just the difference
+    let mut preceding = 0;
+    let mut sum = 0;
+    for (i, &coord) in coords.iter().enumerate() {
+        sum += coord * i - preceding;
+        preceding += coord;
     }
+    sum
+}
 
-    steps as i64
+/// Returns the sum of the shortest paths between all pairs of galaxies, with every empty row and column
+/// replaced by `expansion_factor` of them.
+/// Part 1 and part 2 are literally the same except for the expansion factor.
+pub fn total_distance(input: &str, expansion_factor: usize) -> i64 {
+    Universe::new(ByteGridView::from(input), expansion_factor).total_distance() as i64
 }
 
 pub fn part1(input: &str) -> i64 {
-    solve(input, 2)
+    total_distance(input, 2)
 }
 
 pub fn part2(input: &str) -> i64 {
-    solve(input, 1_000_000)
+    total_distance(input, 1_000_000)
 }
 
 #[cfg(test)]
 #[test]
 fn p11t() {
     const INPUT: &str = include_str!("../inputs/day11.txt");
+    const SAMPLE: &str = "...#......
+.......#..
+#.........
+..........
+......#...
+.#........
+.........#
+..........
+.......#..
+#...#.....";
+    assert_eq!(total_distance(SAMPLE, 2), 374);
+    assert_eq!(total_distance(SAMPLE, 10), 1030);
+    assert_eq!(total_distance(SAMPLE, 100), 8410);
+
+    let universe = Universe::new(ByteGridView::from(SAMPLE), 2);
+    assert_eq!(
+        universe.galaxies()[..3],
+        [
+            Position { y: 0, x: 4 },
+            Position { y: 1, x: 9 },
+            Position { y: 2, x: 0 }
+        ]
+    );
+    assert_eq!(universe.distance(4, 8), 9);
+    assert_eq!(universe.distance(0, 6), 15);
+    assert_eq!(universe.distance(2, 5), 17);
+    assert_eq!(universe.distance(7, 8), 5);
+    assert_eq!(universe.total_distance(), 374);
+    let (a, b) = universe.closest_pair().unwrap();
+    assert_eq!(universe.distance(a, b), 5);
+    assert_eq!(universe.farthest_pair(), Some((1, 7)));
+    assert_eq!(universe.distance(1, 7), 19);
+    assert_eq!(
+        universe.render(),
+        "....#........
+.........#...
+#............
+.............
+.............
+........#....
+.#...........
+............#
+.............
+.............
+.........#...
+#....#.......
+"
+    );
+
+    // Compare with checking every pair
+    let check_farthest = |universe: &Universe| {
+        let max = (0..universe.galaxies().len())
+            .tuple_combinations()
+            .map(|(a, b)| universe.distance(a, b))
+            .max();
+        let (a, b) = universe.farthest_pair().unwrap();
+        assert_eq!(Some(universe.distance(a, b)), max);
+    };
+    check_farthest(&universe);
+    // Both diagonals are equally long
+    let corners = Universe::new(ByteGridView::from("#.#\n...\n#.#"), 2);
+    check_farthest(&corners);
+    assert!([(0, 3), (1, 2)].contains(&corners.farthest_pair().unwrap()));
+    check_farthest(&Universe::new(ByteGridView::from(INPUT.trim()), 2));
+
     assert_eq!(part1(INPUT.trim()), 9509330);
     assert_eq!(part2(INPUT.trim()), 635832237682);
 }
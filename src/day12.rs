@@ -30,69 +30,90 @@ impl Spring {
     }
 }
 
-pub fn part1(input: &str) -> i64 {
-    let lines = input.lines().map(|line| {
-        let (springs, damaged) = line.split_once(' ').unwrap();
-        let springs = springs
-            .bytes()
-            .map(|b| match b {
-                b'#' => Spring::Broken,
-                b'.' => Spring::Operational,
-                b'?' => Spring::Unknown,
-                _ => unreachable!(),
-            })
-            .collect_vec();
-        let damaged = damaged
-            .split(',')
-            .map(|s| s.parse::<i64>().unwrap())
-            .collect_vec();
-        (springs, damaged)
-    });
-
-    let mut arrangements = 0;
-    for (springs, damaged) in lines {
-        let mut queue: Vec<(&[Spring], &[i64])> = vec![(&springs, &damaged)];
+/// Counts the ways in which the unknown springs can be filled in so that the runs of broken springs
+/// have exactly the lengths in `groups`, in order.
+fn count_arrangements(springs: &[Spring], groups: &[usize]) -> i64 {
+    // ways[i][g] is the number of arrangements of `springs[i..]` that match `groups[g..]`.
+    // Filled in back to front, so each entry only depends on entries that are already known.
+    let mut ways = vec![vec![0i64; groups.len() + 1]; springs.len() + 1];
+    ways[springs.len()][groups.len()] = 1;
 
-        while let Some((springs, damaged)) = queue.pop() {
-            for (start, window) in springs.windows(damaged[0] as usize).enumerate() {
-                let end = start + damaged[0] as usize - 1;
-                let surrounded_by_damaged = start
-                    .checked_sub(1)
-                    .is_some_and(|i| springs[i] == Spring::Broken)
-                    || springs.get(end + 1).is_some_and(|&s| s == Spring::Broken);
+    for i in (0..springs.len()).rev() {
+        for g in 0..=groups.len() {
+            let mut count = 0;
 
-                if !surrounded_by_damaged && window.iter().all(|&s| s.maybe_broken()) {
-                    if damaged.len() == 1 {
-                        // Path can't be used if there are still broken ones, we were too eager
-                        if !springs[end + 1..].iter().any(|&v| v == Spring::Broken) {
-                            // Last one found.
-                            arrangements += 1;
-                        }
-                    } else if let Some(post) = springs.get(end + 2..) {
-                        queue.push((post, &damaged[1..]));
-                    }
-                }
+            if springs[i] != Spring::Broken {
+                // This one is operational
+                count += ways[i + 1][g];
+            }
 
-                if window[0] == Spring::Broken {
-                    break;
-                }
+            if let Some(&len) = groups.get(g)
+                && let Some(window) = springs.get(i..i + len)
+                && window.iter().all(|&s| s.maybe_broken())
+                && springs.get(i + len) != Some(&Spring::Broken)
+            {
+                // The next group starts here, and the spring after it (if any) must be operational
+                count += ways[(i + len + 1).min(springs.len())][g + 1];
             }
+
+            ways[i][g] = count;
         }
     }
 
-    arrangements
+    ways[0][0]
+}
+
+/// Sums up the arrangements of all records, after unfolding them `unfold` times
+fn solve(input: &str, unfold: usize) -> i64 {
+    input
+        .lines()
+        .map(|line| {
+            let (springs, damaged) = line.split_once(' ').unwrap();
+            let springs = springs
+                .bytes()
+                .map(|b| match b {
+                    b'#' => Spring::Broken,
+                    b'.' => Spring::Operational,
+                    b'?' => Spring::Unknown,
+                    _ => unreachable!(),
+                })
+                .collect_vec();
+            let damaged = damaged
+                .split(',')
+                .map(|s| s.parse::<usize>().unwrap())
+                .collect_vec();
+
+            let springs = vec![springs; unfold].join(&Spring::Unknown);
+            let damaged = damaged.repeat(unfold);
+
+            count_arrangements(&springs, &damaged)
+        })
+        .sum()
+}
+
+pub fn part1(input: &str) -> i64 {
+    solve(input, 1)
 }
 
-#[allow(clippy::redundant_locals)]
-pub fn _part2(_input: &str) -> i64 {
-    // TODO: make it run in a reasonable amount of time
-    todo!("commit once cleaned up")
+pub fn part2(input: &str) -> i64 {
+    solve(input, 5)
 }
 
 #[cfg(test)]
 #[test]
 fn p12t() {
     const INPUT: &str = include_str!("../inputs/day12.txt");
+    const SAMPLE: &str = "???.### 1,1,3
+.??..??...?##. 1,1,3
+?#?#?#?#?#?#?#? 1,3,1,6
+????.#...#... 4,1,1
+????.######..#####. 1,6,5
+?###???????? 3,2,1";
+    assert_eq!(part1("???.### 1,1,3"), 1);
+    assert_eq!(part1("?###???????? 3,2,1"), 10);
+    assert_eq!(part1(SAMPLE), 21);
     assert_eq!(part1(INPUT), 7195);
-    // assert_eq!(part2(INPUT), 33992866292225);
+    assert_eq!(part2("?###???????? 3,2,1"), 506250);
+    assert_eq!(part2(SAMPLE), 525152);
+    assert_eq!(part2(INPUT), 33992866292225);
 }
@@ -1,180 +1,156 @@
 use crate::grid::ByteGridView;
+use crate::grid::Position;
+use crate::grid::TransformedGridView;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Orientation {
+    /// The line goes between two rows
+    Horizontal,
+    /// The line goes between two columns
+    Vertical,
+}
 
-pub fn part1(input: &str) -> i64 {
-    let mut sum = 0;
-
-    for pattern in input.split("\n\n") {
-        let grid = ByteGridView::from(pattern);
-
-        // horizontal top side
-        let mut mirror = grid.rows() / 2;
-        while mirror >= 1 {
-            if (0..mirror)
-                .zip((0..mirror * 2).rev())
-                .all(|(r1, r2)| grid[r1] == grid[r2])
-            {
-                sum += 100 * mirror as i64;
-                break;
-            }
-
-            mirror -= 1;
-        }
-
-        // horizontal bottom side
-        let mut mirror = grid.rows() / 2;
-        while mirror >= 1 {
-            if (grid.rows() - mirror..grid.rows())
-                .rev()
-                .zip(grid.rows() - mirror * 2..grid.rows() - mirror)
-                .all(|(r1, r2)| grid[r1] == grid[r2])
-            {
-                sum += 100 * (grid.rows() - mirror) as i64;
-                break;
-            }
-
-            mirror -= 1;
-        }
-
-        // vertical left side
-        let mut mirror = grid.columns() / 2;
-        while mirror >= 1 {
-            if (0..mirror)
-                .zip((0..mirror * 2).rev())
-                .all(|(c1, c2)| (0..grid.rows()).all(|row| grid[row][c1] == grid[row][c2]))
-            {
-                sum += mirror as i64;
-                break;
-            }
-            mirror -= 1;
-        }
+/// A line that a pattern is (almost) mirrored across
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reflection {
+    pub orientation: Orientation,
+    /// The number of rows above (or columns left of) the line
+    pub axis: usize,
+    /// Every pair of tiles on opposite sides of the line that don't match. Flipping either tile of every
+    /// pair makes the reflection perfect.
+    pub smudges: Vec<(Position, Position)>,
+}
 
-        // vertical right side
-        let mut mirror = grid.columns() / 2;
-        while mirror >= 1 {
-            if (grid.columns() - mirror..grid.columns())
-                .rev()
-                .zip(grid.columns() - mirror * 2..grid.columns() - mirror)
-                .all(|(c1, c2)| (0..grid.rows()).all(|row| grid[row][c1] == grid[row][c2]))
-            {
-                sum += (grid.columns() - mirror) as i64;
-                break;
-            }
-            mirror -= 1;
+impl Reflection {
+    /// The number that the puzzle wants for this reflection
+    pub fn summary(&self) -> i64 {
+        match self.orientation {
+            Orientation::Horizontal => 100 * self.axis as i64,
+            Orientation::Vertical => self.axis as i64,
         }
     }
-
-    sum
 }
 
-pub fn part2(input: &str) -> i64 {
-    let mut sum = 0;
-
-    for pattern in input.split("\n\n") {
-        fn winnow_row(grid: ByteGridView<'_>, iter: impl Iterator<Item = (usize, usize)>) -> bool {
-            let mut mirrored = true;
-            let mut used_smudge: bool = false;
-
-            for (r1, r2) in iter {
-                let wrong_ones = grid[r1]
-                    .iter()
-                    .zip(&grid[r2])
-                    .filter(|(a, b)| a != b)
-                    .count();
-
-                match wrong_ones {
-                    0 => {}
-                    1 if !used_smudge => {
-                        used_smudge = true;
-                    }
-                    _ => {
-                        mirrored = false;
-                        break;
+/// Finds a line between two rows of the view that has exactly `mismatches` tiles that don't match their
+/// reflection. Returns the line and the mismatching tiles in terms of the underlying grid.
+fn find_row_reflection(
+    view: TransformedGridView<'_>,
+    mismatches: usize,
+) -> Option<(usize, Vec<(Position, Position)>)> {
+    (1..view.rows()).find_map(|axis| {
+        let mut smudges = Vec::new();
+
+        // Rows past the edge on either side have nothing to be compared with
+        for (r1, r2) in (0..axis).rev().zip(axis..view.rows()) {
+            for col in 0..view.columns() {
+                if view.get(r1, col) != view.get(r2, col) {
+                    if smudges.len() == mismatches {
+                        return None;
                     }
+                    smudges.push((view.source_position(r1, col), view.source_position(r2, col)));
                 }
             }
-            mirrored && used_smudge
         }
 
-        fn winnow_col(grid: ByteGridView<'_>, iter: impl Iterator<Item = (usize, usize)>) -> bool {
-            let mut mirrored = true;
-            let mut used_smudge = false;
-
-            for (c1, c2) in iter {
-                let wrong_ones = (0..grid.rows())
-                    .filter(|&row| grid[row][c1] != grid[row][c2])
-                    .count();
-
-                match wrong_ones {
-                    0 => {}
-                    1 if !used_smudge => {
-                        used_smudge = true;
-                    }
-                    _ => {
-                        mirrored = false;
-                        break;
-                    }
-                }
-            }
-
-            mirrored && used_smudge
-        }
-
-        let grid = ByteGridView::from(pattern);
-
-        // horizontal top side
-        let mut mirror = grid.rows() / 2;
-        while mirror >= 1 {
-            if winnow_row(grid, (0..mirror).zip((0..mirror * 2).rev())) {
-                sum += (100 * (mirror)) as i64;
-            }
-            mirror -= 1;
-        }
+        (smudges.len() == mismatches).then_some((axis, smudges))
+    })
+}
 
-        // horizontal bottom side
-        let mut mirror = grid.rows() / 2;
-        while mirror >= 1 {
-            if winnow_row(
-                grid,
-                (grid.rows() - mirror..grid.rows())
-                    .rev()
-                    .zip(grid.rows() - mirror * 2..grid.rows() - mirror),
-            ) {
-                sum += (100 * (grid.rows() - mirror)) as i64;
-            }
-            mirror -= 1;
-        }
+/// Finds a horizontal or vertical line that the pattern is mirrored across with exactly `mismatches`
+/// smudges, i.e. tiles that don't match their reflection. Horizontal lines are looked for first.
+pub fn find_reflection(grid: ByteGridView<'_>, mismatches: usize) -> Option<Reflection> {
+    [
+        (Orientation::Horizontal, grid.view()),
+        (Orientation::Vertical, grid.transpose()),
+    ]
+    .into_iter()
+    .find_map(|(orientation, view)| {
+        find_row_reflection(view, mismatches).map(|(axis, smudges)| Reflection {
+            orientation,
+            axis,
+            smudges,
+        })
+    })
+}
 
-        // vertical left side
-        let mut mirror = grid.columns() / 2;
-        while mirror >= 1 {
-            if winnow_col(grid, (0..mirror).zip((0..mirror * 2).rev())) {
-                sum += mirror as i64;
-            }
-            mirror -= 1;
-        }
+fn solve(input: &str, mismatches: usize) -> i64 {
+    input
+        .split("\n\n")
+        .map(|pattern| {
+            find_reflection(ByteGridView::from(pattern), mismatches)
+                .expect("pattern without a reflection")
+                .summary()
+        })
+        .sum()
+}
 
-        // vertical right side
-        let mut mirror = grid.columns() / 2;
-        while mirror >= 1 {
-            if winnow_col(
-                grid,
-                (grid.columns() - mirror..grid.columns())
-                    .rev()
-                    .zip(grid.columns() - mirror * 2..grid.columns() - mirror),
-            ) {
-                sum += (grid.columns() - mirror) as i64;
-            }
+pub fn part1(input: &str) -> i64 {
+    solve(input, 0)
+}
 
-            mirror -= 1;
-        }
-    }
-    sum
+pub fn part2(input: &str) -> i64 {
+    // Exactly one smudge needs fixing, which also makes the old reflection line invalid
+    solve(input, 1)
 }
 
 #[cfg(test)]
 #[test]
 fn p13t() {
     const INPUT: &str = include_str!("../inputs/day13.txt");
+    const SAMPLE: &str = "#.##..##.
+..#.##.#.
+##......#
+##......#
+..#.##.#.
+..##..##.
+#.#.##.#.
+
+#...##..#
+#....#..#
+..##..###
+#####.##.
+#####.##.
+..##..###
+#....#..#";
+    assert_eq!(part1(SAMPLE), 405);
+    assert_eq!(part2(SAMPLE), 400);
+
+    let (first, second) = SAMPLE.split_once("\n\n").unwrap();
+    let (first, second) = (ByteGridView::from(first), ByteGridView::from(second));
+    assert_eq!(
+        find_reflection(first, 0),
+        Some(Reflection {
+            orientation: Orientation::Vertical,
+            axis: 5,
+            smudges: Vec::new()
+        })
+    );
+    assert_eq!(
+        find_reflection(first, 1),
+        Some(Reflection {
+            orientation: Orientation::Horizontal,
+            axis: 3,
+            smudges: vec![(Position { y: 0, x: 0 }, Position { y: 5, x: 0 })]
+        })
+    );
+    assert_eq!(
+        find_reflection(second, 1),
+        Some(Reflection {
+            orientation: Orientation::Horizontal,
+            axis: 1,
+            smudges: vec![(Position { y: 0, x: 4 }, Position { y: 1, x: 4 })]
+        })
+    );
+    let reflection = find_reflection(second, 2).unwrap();
+    assert_eq!(
+        (reflection.orientation, reflection.axis),
+        (Orientation::Vertical, 7)
+    );
+    assert_eq!(reflection.smudges.len(), 2);
+    for (a, b) in reflection.smudges {
+        assert_ne!(second[a.y][a.x], second[b.y][b.x]);
+    }
+
     assert_eq!(part1(INPUT.trim()), 43614);
     assert_eq!(part2(INPUT.trim()), 36771);
 }
@@ -3,7 +3,10 @@ use std::fmt::Debug;
 use itertools::Itertools;
 use memchr::memchr;
 
-#[derive(Copy, Clone, PartialEq, Eq)]
+use crate::cycle;
+use crate::grid::Direction;
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
 enum Rock {
     Rounded,
     Cube,
@@ -20,53 +23,122 @@ impl Debug for Rock {
     }
 }
 
-pub fn part1(input: &str) -> i64 {
-    let mut grid = input
-        .bytes()
-        .filter(|&b| b != b'\n')
-        .map(|c| match c {
-            b'#' => Rock::Cube,
-            b'O' => Rock::Rounded,
-            b'.' => Rock::Space,
-            _ => unreachable!(),
-        })
-        .collect_vec();
-
-    // TODO: write something like ByteGridView but allowing mutability
-    let columns = memchr(b'\n', input.as_bytes()).unwrap();
-    let rows = input[columns + 1..].bytes().filter(|&v| v == b'\n').count() + 2;
-    let toi = |r, c| r * columns + c;
-    let mut sum = 0;
-
-    for row in 0..rows {
-        for col in 0..columns {
-            if let Rock::Rounded = grid[toi(row, col)] {
-                if row > 0
-                    && let Some(nrow) = (0..=row - 1)
-                        .rev()
-                        .take_while(|&row| grid[toi(row, col)] == Rock::Space)
-                        .last()
-                {
-                    sum += (rows - nrow) as i64;
-                    grid[toi(row, col)] = Rock::Space;
-                    grid[toi(nrow, col)] = Rock::Rounded;
-                } else {
-                    sum += (rows - row) as i64;
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct Platform {
+    rocks: Vec<Rock>,
+    rows: usize,
+    columns: usize,
+}
+
+impl Platform {
+    fn parse(input: &str) -> Self {
+        let rocks = input
+            .bytes()
+            .filter(|&b| b != b'\n')
+            .map(|c| match c {
+                b'#' => Rock::Cube,
+                b'O' => Rock::Rounded,
+                b'.' => Rock::Space,
+                _ => unreachable!(),
+            })
+            .collect_vec();
+
+        let columns = memchr(b'\n', input.as_bytes()).unwrap_or(input.len());
+        let rows = rocks.len() / columns;
+
+        Self {
+            rocks,
+            rows,
+            columns,
+        }
+    }
+
+    /// Tilts the platform so that all rounded rocks roll as far as they can in the given direction
+    fn tilt(&mut self, direction: Direction) {
+        let (lanes, lane_len) = match direction {
+            Direction::Up | Direction::Down => (self.columns, self.rows),
+            Direction::Left | Direction::Right => (self.rows, self.columns),
+        };
+
+        // Maps the k-th tile of a lane, counting from the edge the rocks roll towards, to its index
+        let rows = self.rows;
+        let columns = self.columns;
+        let toi = move |lane: usize, k: usize| match direction {
+            Direction::Up => k * columns + lane,
+            Direction::Down => (rows - 1 - k) * columns + lane,
+            Direction::Left => lane * columns + k,
+            Direction::Right => lane * columns + (columns - 1 - k),
+        };
+
+        for lane in 0..lanes {
+            // Where the next rounded rock in this lane comes to a stop
+            let mut free = 0;
+
+            for k in 0..lane_len {
+                match self.rocks[toi(lane, k)] {
+                    Rock::Cube => free = k + 1,
+                    Rock::Rounded => {
+                        self.rocks[toi(lane, k)] = Rock::Space;
+                        self.rocks[toi(lane, free)] = Rock::Rounded;
+                        free += 1;
+                    }
+                    Rock::Space => {}
                 }
             }
         }
     }
 
-    sum
+    fn spin_cycle(&mut self) {
+        for direction in [
+            Direction::Up,
+            Direction::Left,
+            Direction::Down,
+            Direction::Right,
+        ] {
+            self.tilt(direction);
+        }
+    }
+
+    fn north_load(&self) -> i64 {
+        self.rocks
+            .iter()
+            .positions(|&rock| rock == Rock::Rounded)
+            .map(|i| (self.rows - i / self.columns) as i64)
+            .sum()
+    }
 }
 
-pub fn _part2(_input: &str) -> i64 {
-    todo!()
+pub fn part1(input: &str) -> i64 {
+    let mut platform = Platform::parse(input);
+    platform.tilt(Direction::Up);
+    platform.north_load()
+}
+
+pub fn part2(input: &str) -> i64 {
+    let spin = |platform: &Platform| {
+        let mut platform = platform.clone();
+        platform.spin_cycle();
+        platform
+    };
+
+    cycle::nth_state(Platform::parse(input), spin, 1_000_000_000).north_load()
 }
 
 #[cfg(test)]
 #[test]
 fn p14t() {
     const INPUT: &str = include_str!("../inputs/day14.txt");
+    const SAMPLE: &str = "O....#....
+O.OO#....#
+.....##...
+OO.#O....O
+.O.....O#.
+O.#..O.#.#
+..O..#O..O
+.......O..
+#....###..
+#OO..#....";
+    assert_eq!(part1(SAMPLE), 136);
     assert_eq!(part1(INPUT.trim()), 106186);
+    assert_eq!(part2(SAMPLE), 64);
 }
@@ -1,210 +1,340 @@
-use std::collections::VecDeque;
+use std::collections::hash_map::Entry;
 
-use rustc_hash::FxHashSet;
+use rayon::iter::ParallelBridge;
+use rayon::iter::ParallelIterator;
+use rustc_hash::FxHashMap;
 
 use crate::grid::ByteGridView;
+use crate::grid::Direction;
+use crate::grid::Grid;
 use crate::grid::Position;
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
-enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
+/// Returns the beams leaving the tile at `pos` when a beam enters it going in direction `dir`
+fn next_beams(
+    grid: ByteGridView<'_>,
+    pos: Position,
+    dir: Direction,
+) -> impl Iterator<Item = (Position, Direction)> + '_ {
+    let outgoing = match (grid[pos.y][pos.x], dir) {
+        (b'.', _)
+        | (b'|', Direction::Up | Direction::Down)
+        | (b'-', Direction::Left | Direction::Right) => [Some(dir), None],
+        (b'|', _) => [Some(Direction::Up), Some(Direction::Down)],
+        (b'-', _) => [Some(Direction::Left), Some(Direction::Right)],
+        (b'/', Direction::Right) => [Some(Direction::Up), None],
+        (b'/', Direction::Up) => [Some(Direction::Right), None],
+        (b'/', Direction::Down) => [Some(Direction::Left), None],
+        (b'/', Direction::Left) => [Some(Direction::Down), None],
+        (b'\\', Direction::Right) => [Some(Direction::Down), None],
+        (b'\\', Direction::Up) => [Some(Direction::Left), None],
+        (b'\\', Direction::Down) => [Some(Direction::Right), None],
+        (b'\\', Direction::Left) => [Some(Direction::Up), None],
+        _ => unreachable!(),
+    };
+
+    outgoing.into_iter().flatten().filter_map(move |dir| {
+        pos.checked_offset(dir)
+            .filter(|&next| grid.in_bounds(next))
+            .map(|next| (next, dir))
+    })
+}
+
+/// A fixed size set of integers below some bound
+struct BitSet {
+    words: Vec<u64>,
 }
 
-fn count_energnized_tiles(
+impl BitSet {
+    fn new(bound: usize) -> Self {
+        Self {
+            words: vec![0; bound.div_ceil(64)],
+        }
+    }
+
+    /// Returns whether the value wasn't in the set yet
+    fn insert(&mut self, value: usize) -> bool {
+        let (word, bit) = (value / 64, 1 << (value % 64));
+        let new = self.words[word] & bit == 0;
+        self.words[word] |= bit;
+        new
+    }
+
+    fn len(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+}
+
+/// The path of a beam up to and including the next splitter (or the edge of the grid)
+struct Segment {
+    /// Indices (`y * columns + x`) of the tiles
+    tiles: Vec<u32>,
+    /// The segments of the beams leaving the splitter at the end
+    next: Vec<usize>,
+}
+
+/// Follows a beam until it hits a splitter or leaves the grid
+fn trace(
     grid: ByteGridView<'_>,
-    start_pos: Position,
-    start_direction: Direction,
-) -> i64 {
-    let mut points = FxHashSet::default();
-    // only used for finding potential cycles
-    let mut seen = FxHashSet::default();
-    let mut queue = VecDeque::new();
-
-    queue.push_back((start_pos, start_direction));
-
-    while let Some((pos, dir)) = queue.pop_front() {
-        if !seen.insert((pos, dir)) {
-            continue; // if we run into a loop, nothing to do really?
+    mut pos: Position,
+    mut dir: Direction,
+) -> (Vec<u32>, Vec<(Position, Direction)>) {
+    // This always ends: a loop of mirrors can be followed backwards, so only a beam starting inside of it
+    // gets caught, and beams start at the edge or next to a splitter
+    let mut tiles = Vec::new();
+    loop {
+        tiles.push((pos.y * grid.columns() + pos.x) as u32);
+
+        let mut beams = next_beams(grid, pos, dir);
+        if matches!(grid[pos.y][pos.x], b'|' | b'-') {
+            return (tiles, beams.collect());
+        }
+        match beams.next() {
+            Some(beam) => (pos, dir) = beam,
+            None => return (tiles, Vec::new()),
         }
-        points.insert(pos);
+    }
+}
 
-        match (grid[pos.y][pos.x], dir) {
-            (b'.', Direction::Right) if !grid.is_right_edge(pos.x) => {
-                queue.push_back((pos.right(), dir))
-            }
-            (b'.', Direction::Down) if !grid.is_bottom_edge(pos.y) => {
-                queue.push_back((pos.down(), dir))
-            }
-            (b'.', Direction::Left) if !grid.is_left_edge(pos.x) => {
-                queue.push_back((pos.left(), dir))
-            }
-            (b'.', Direction::Up) if !grid.is_top_edge(pos.y) => queue.push_back((pos.up(), dir)),
-            (b'.', _) => {}
-            (b'|', Direction::Right | Direction::Left) => {
-                if !grid.is_top_edge(pos.y) {
-                    queue.push_back((pos.up(), Direction::Up));
+/// The segments of all beams leaving a splitter, shared by every beam entering the grid
+struct Contraption<'a> {
+    grid: ByteGridView<'a>,
+    segments: Vec<Segment>,
+    segment_ids: FxHashMap<(Position, Direction), usize>,
+}
+
+impl<'a> Contraption<'a> {
+    fn new(grid: ByteGridView<'a>) -> Self {
+        let mut starts = Vec::new();
+        let mut segment_ids = FxHashMap::default();
+
+        for y in 0..grid.rows() {
+            for x in 0..grid.columns() {
+                if !matches!(grid[y][x], b'|' | b'-') {
+                    continue;
                 }
-                if !grid.is_bottom_edge(pos.y) {
-                    queue.push_back((pos.down(), Direction::Down));
+
+                for dir in Direction::ALL {
+                    for beam in next_beams(grid, Position { y, x }, dir) {
+                        if let Entry::Vacant(entry) = segment_ids.entry(beam) {
+                            entry.insert(starts.len());
+                            starts.push(beam);
+                        }
+                    }
                 }
             }
-            (b'|', Direction::Up) if !grid.is_top_edge(pos.y) => queue.push_back((pos.up(), dir)),
-            (b'|', Direction::Down) if !grid.is_bottom_edge(pos.y) => {
-                queue.push_back((pos.down(), dir))
-            }
-            (b'|', _) => {}
-            (b'-', Direction::Up | Direction::Down) => {
-                if !grid.is_left_edge(pos.x) {
-                    queue.push_back((pos.left(), Direction::Left));
-                }
-                if !grid.is_right_edge(pos.x) {
-                    queue.push_back((pos.right(), Direction::Right));
+        }
+
+        let segments = starts
+            .into_iter()
+            .map(|(pos, dir)| {
+                let (tiles, next) = trace(grid, pos, dir);
+                Segment {
+                    tiles,
+                    next: next.iter().map(|beam| segment_ids[beam]).collect(),
                 }
+            })
+            .collect();
+
+        Self {
+            grid,
+            segments,
+            segment_ids,
+        }
+    }
+
+    /// Returns the tiles energized by a beam entering the grid at `pos`, going in direction `dir`
+    fn energized(&self, pos: Position, dir: Direction) -> BitSet {
+        let mut energized = BitSet::new(self.grid.rows() * self.grid.columns());
+        // Beams running into a loop are fine, every segment is only followed once
+        let mut seen = BitSet::new(self.segments.len());
+
+        let (tiles, next) = trace(self.grid, pos, dir);
+        let mut stack = next
+            .iter()
+            .map(|beam| self.segment_ids[beam])
+            .collect::<Vec<_>>();
+        for tile in tiles {
+            energized.insert(tile as usize);
+        }
+
+        while let Some(id) = stack.pop() {
+            if !seen.insert(id) {
+                continue;
             }
-            (b'-', Direction::Right) if !grid.is_right_edge(pos.x) => {
-                queue.push_back((pos.right(), dir));
-            }
-            (b'-', Direction::Left) if !grid.is_left_edge(pos.x) => {
-                queue.push_back((pos.left(), dir));
-            }
-            (b'-', _) => {}
-            (b'/', Direction::Right) if !grid.is_top_edge(pos.y) => {
-                queue.push_back((pos.up(), Direction::Up));
-            }
-            (b'/', Direction::Up) if !grid.is_right_edge(pos.x) => {
-                queue.push_back((pos.right(), Direction::Right));
-            }
-            (b'/', Direction::Down) if !grid.is_left_edge(pos.x) => {
-                queue.push_back((pos.left(), Direction::Left));
-            }
-            (b'/', Direction::Left) if !grid.is_bottom_edge(pos.y) => {
-                queue.push_back((pos.down(), Direction::Down));
-            }
-            (b'/', _) => {}
-            (b'\\', Direction::Right) if !grid.is_bottom_edge(pos.y) => {
-                queue.push_back((pos.down(), Direction::Down));
-            }
-            (b'\\', Direction::Up) if !grid.is_left_edge(pos.x) => {
-                queue.push_back((pos.left(), Direction::Left));
-            }
-            (b'\\', Direction::Down) if !grid.is_right_edge(pos.x) => {
-                queue.push_back((pos.right(), Direction::Right));
-            }
-            (b'\\', Direction::Left) if !grid.is_top_edge(pos.y) => {
-                queue.push_back((pos.up(), Direction::Up));
+
+            let segment = &self.segments[id];
+            for &tile in &segment.tiles {
+                energized.insert(tile as usize);
             }
-            (b'\\', _) => {}
-            _ => unreachable!(),
+            stack.extend(&segment.next);
         }
+
+        energized
     }
+}
 
-    points.len() as i64
+/// Where beams went in the contraption, for drawing it and finding out where beams go in circles
+pub struct BeamTrace {
+    rows: usize,
+    columns: usize,
+    /// One bit per [`Direction::ALL`] for every tile
+    directions: Vec<u8>,
+    loops: Vec<(Position, Direction)>,
 }
 
-pub fn part1(input: &str) -> i64 {
-    let grid = ByteGridView::from(input);
-    count_energnized_tiles(grid, Position { y: 0, x: 0 }, Direction::Right)
+impl BeamTrace {
+    pub fn is_energized(&self, pos: Position) -> bool {
+        self.directions[pos.y * self.columns + pos.x] != 0
+    }
+
+    /// Returns every tile a beam passed through
+    pub fn energized(&self) -> impl Iterator<Item = Position> + '_ {
+        (0..self.rows)
+            .flat_map(move |y| (0..self.columns).map(move |x| Position { y, x }))
+            .filter(|&pos| self.is_energized(pos))
+    }
+
+    /// Returns the directions beams went through the tile at `pos` in
+    pub fn directions(&self, pos: Position) -> impl Iterator<Item = Direction> {
+        let bits = self.directions[pos.y * self.columns + pos.x];
+        Direction::ALL
+            .into_iter()
+            .enumerate()
+            .filter(move |&(i, _)| bits & 1 << i != 0)
+            .map(|(_, dir)| dir)
+    }
+
+    /// Returns where beams came back to a tile and direction that led to them, going in circles forever
+    pub fn loops(&self) -> &[(Position, Direction)] {
+        &self.loops
+    }
 }
 
-pub fn part2(input: &str) -> i64 {
-    let grid = ByteGridView::from(input);
+/// Follows a beam entering the grid at `pos` going in direction `dir`
+pub fn trace_beam(grid: ByteGridView<'_>, pos: Position, dir: Direction) -> BeamTrace {
+    #[derive(Clone, Copy, PartialEq)]
+    enum Mark {
+        Unvisited,
+        OnPath,
+        Done,
+    }
 
-    let mut combinations = vec![
-        (Position { y: 0, x: 0 }, Direction::Right),
-        (Position { y: 0, x: 0 }, Direction::Down),
-        (
-            Position {
-                y: 0,
-                x: grid.columns() - 1,
-            },
-            Direction::Left,
-        ),
-        (
-            Position {
-                y: 0,
-                x: grid.columns() - 1,
-            },
-            Direction::Down,
-        ),
-        (
-            Position {
-                y: grid.rows() - 1,
-                x: 0,
-            },
-            Direction::Right,
-        ),
-        (
-            Position {
-                y: grid.rows() - 1,
-                x: 0,
-            },
-            Direction::Up,
-        ),
-        (
-            Position {
-                y: grid.rows() - 1,
-                x: grid.columns() - 1,
-            },
-            Direction::Left,
-        ),
-        (
-            Position {
-                y: grid.rows() - 1,
-                x: grid.columns() - 1,
-            },
-            Direction::Up,
-        ),
-    ];
-
-    // top and bottom edge
-    for x in 1..grid.columns() - 1 {
-        combinations.push((Position { y: 0, x }, Direction::Down));
-        combinations.push((Position { y: 0, x }, Direction::Left));
-        combinations.push((Position { y: 0, x }, Direction::Right));
-
-        combinations.push((
-            Position {
-                y: grid.rows() - 1,
-                x,
-            },
-            Direction::Up,
-        ));
-        combinations.push((
-            Position {
-                y: grid.rows() - 1,
-                x,
-            },
-            Direction::Left,
-        ));
-        combinations.push((
-            Position {
-                y: grid.rows() - 1,
-                x,
+    let bit = |dir: Direction| Direction::ALL.iter().position(|&d| d == dir).unwrap();
+    let index = |pos: Position, dir: Direction| (pos.y * grid.columns() + pos.x) * 4 + bit(dir);
+
+    let mut trace = BeamTrace {
+        rows: grid.rows(),
+        columns: grid.columns(),
+        directions: vec![0; grid.rows() * grid.columns()],
+        loops: Vec::new(),
+    };
+    let mut marks = vec![Mark::Unvisited; grid.rows() * grid.columns() * 4];
+
+    // Depth-first, so that the beams on the stack are exactly the path to the current one. Running into one
+    // of them means the beam went around in a circle.
+    let mut stack = vec![(pos, dir, 0)];
+    marks[index(pos, dir)] = Mark::OnPath;
+    trace.directions[pos.y * grid.columns() + pos.x] |= 1 << bit(dir);
+
+    while let Some((pos, dir, child)) = stack.last_mut() {
+        let next = next_beams(grid, *pos, *dir).nth(*child);
+        *child += 1;
+
+        match next {
+            None => {
+                marks[index(*pos, *dir)] = Mark::Done;
+                stack.pop();
+            }
+            Some((next, dir)) => match marks[index(next, dir)] {
+                Mark::Unvisited => {
+                    marks[index(next, dir)] = Mark::OnPath;
+                    trace.directions[next.y * grid.columns() + next.x] |= 1 << bit(dir);
+                    stack.push((next, dir, 0));
+                }
+                Mark::OnPath => trace.loops.push((next, dir)),
+                Mark::Done => {}
             },
-            Direction::Right,
-        ));
+        }
     }
 
-    // left and right edge
-    for y in 1..grid.rows() - 1 {
-        combinations.push((Position { y, x: 0 }, Direction::Up));
-        combinations.push((Position { y, x: 0 }, Direction::Right));
-        combinations.push((Position { y, x: 0 }, Direction::Down));
+    trace
+}
+
+/// Draws the energized tiles as `#` and all other tiles as `.`
+pub fn render_energized(trace: &BeamTrace) -> String {
+    let mut out = String::new();
+    for y in 0..trace.rows {
+        for x in 0..trace.columns {
+            out.push(if trace.is_energized(Position { y, x }) {
+                '#'
+            } else {
+                '.'
+            });
+        }
+        out.push('\n');
+    }
+    out
+}
 
-        let x = grid.columns() - 1;
-        combinations.push((Position { y, x }, Direction::Up));
-        combinations.push((Position { y, x }, Direction::Left));
-        combinations.push((Position { y, x }, Direction::Down));
+/// Draws the grid with an arrow (or the number of directions) on every empty tile that beams went through
+pub fn render_beams(grid: ByteGridView<'_>, trace: &BeamTrace) -> String {
+    let mut out = String::new();
+    for y in 0..grid.rows() {
+        for x in 0..grid.columns() {
+            let mut directions = trace.directions(Position { y, x });
+            out.push(match (grid[y][x], directions.next(), directions.count()) {
+                (b'.', Some(Direction::Up), 0) => '^',
+                (b'.', Some(Direction::Down), 0) => 'v',
+                (b'.', Some(Direction::Left), 0) => '<',
+                (b'.', Some(Direction::Right), 0) => '>',
+                (b'.', Some(_), more) => char::from_digit(more as u32 + 1, 10).unwrap(),
+                (c, ..) => c as char,
+            });
+        }
+        out.push('\n');
     }
+    out
+}
+
+/// Returns every beam that can enter the grid from the outside
+fn entry_beams(grid: ByteGridView<'_>) -> impl Iterator<Item = (Position, Direction)> {
+    let (bottom, right) = (grid.rows() - 1, grid.columns() - 1);
+
+    let vertical = (0..grid.columns()).flat_map(move |x| {
+        [
+            (Position { y: 0, x }, Direction::Down),
+            (Position { y: bottom, x }, Direction::Up),
+        ]
+    });
+    let horizontal = (0..grid.rows()).flat_map(move |y| {
+        [
+            (Position { y, x: 0 }, Direction::Right),
+            (Position { y, x: right }, Direction::Left),
+        ]
+    });
+
+    vertical.chain(horizontal)
+}
 
-    combinations
-        .into_iter()
-        .map(|(pos, dir)| count_energnized_tiles(grid, pos, dir))
+pub fn part1(input: &str) -> i64 {
+    let grid = ByteGridView::from(input);
+    Contraption::new(grid)
+        .energized(Position { y: 0, x: 0 }, Direction::Right)
+        .len() as i64
+}
+
+pub fn part2(input: &str) -> i64 {
+    let grid = ByteGridView::from(input);
+    let contraption = Contraption::new(grid);
+
+    entry_beams(grid)
+        .par_bridge()
+        .map(|(pos, dir)| contraption.energized(pos, dir).len())
         .max()
-        .unwrap()
+        .unwrap() as i64
 }
 
 #[cfg(test)]
@@ -225,5 +355,64 @@ fn p16t() {
 "#;
     assert_eq!(part1(INPUT.trim()), 6994);
     assert_eq!(part1(SAMPLE.trim()), 46);
+    assert_eq!(part2(SAMPLE.trim()), 51);
     assert_eq!(part2(INPUT.trim()), 7488);
+
+    let grid = ByteGridView::from(SAMPLE.trim());
+
+    let trace = trace_beam(grid, Position { y: 0, x: 0 }, Direction::Right);
+    assert_eq!(
+        render_energized(&trace),
+        "######....
+.#...#....
+.#...#####
+.#...##...
+.#...##...
+.#...##...
+.#..####..
+########..
+.#######..
+.#...#.#..
+"
+    );
+    assert_eq!(
+        render_beams(grid, &trace),
+        r">|<<<\....
+|v-.\^....
+.v...|->>>
+.v...v^.|.
+.v...v^...
+.v...v^..\
+.v../2\\..
+<->-/vv|..
+.|<<<2-|.\
+.v//.|.v..
+"
+    );
+
+    // Going right along the top, splitting at the bottom left and coming back to the top
+    let looping = ByteGridView::from(".-.\\\n....\n.\\./");
+    let trace = trace_beam(looping, Position { y: 0, x: 0 }, Direction::Right);
+    assert_eq!(trace.loops(), [(Position { y: 0, x: 2 }, Direction::Right)]);
+    assert_eq!(trace.energized().count(), 9);
+    assert_eq!(
+        trace
+            .directions(Position { y: 0, x: 0 })
+            .collect::<Vec<_>>(),
+        [Direction::Right, Direction::Left]
+    );
+
+    // Compare with simply following every beam one tile at a time
+    let contraption = Contraption::new(grid);
+    assert_eq!(entry_beams(grid).count(), 40);
+    for (pos, dir) in entry_beams(grid) {
+        let expected =
+            crate::search::reachable([(pos, dir)], |&(pos, dir)| next_beams(grid, pos, dir))
+                .into_iter()
+                .map(|(pos, _)| pos)
+                .collect::<rustc_hash::FxHashSet<_>>()
+                .len();
+        assert_eq!(contraption.energized(pos, dir).len(), expected);
+        assert_eq!(trace_beam(grid, pos, dir).energized().count(), expected);
+    }
 }
@@ -1,220 +1,245 @@
-use std::collections::hash_map::Entry;
-use std::collections::VecDeque;
+use std::cmp::Ordering;
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fmt::Write;
+use std::ops::RangeInclusive;
 
 use owo_colors::OwoColorize;
 use rustc_hash::FxHashMap;
-use rustc_hash::FxHashSet;
 
 use crate::grid::ByteGridView;
 use crate::grid::Direction;
+use crate::grid::Grid;
 use crate::grid::Position;
 
-#[derive(Copy, Clone, Debug)]
-struct BfsState {
-    pos: Position,
-    dir: Direction,
-    steps_taken: u32,
-    heat_loss: i64,
-    parent_path: Option<usize>,
+/// How far the crucible from part 1 can move in a straight line
+pub const CRUCIBLE: RangeInclusive<usize> = 1..=3;
+/// How far the ultra crucible from part 2 can move in a straight line
+pub const ULTRA_CRUCIBLE: RangeInclusive<usize> = 4..=10;
+
+/// A block on the route, and the direction the crucible moved in to get there
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Step {
+    pub pos: Position,
+    pub direction: Direction,
 }
 
-#[allow(dead_code, reason = "debugging")]
-fn visualize(grid: ByteGridView<'_>, path: &[Position]) {
-    let points = FxHashSet::from_iter(path.iter().copied());
-    for y in 0..grid.rows() {
-        for x in 0..grid.columns() {
-            if points.contains(&Position { y, x }) {
-                print!("{}", "x".red().bold());
-            } else {
-                print!("{}", grid[y][x] as char);
-            }
-        }
-        println!();
-    }
+#[derive(Debug, Clone)]
+pub struct Route {
+    pub heat_loss: i64,
+    /// Every block the crucible enters, in order. The top left block it starts on isn't included.
+    pub steps: Vec<Step>,
 }
 
-#[allow(dead_code, reason = "debugging")]
-fn reconstruct_path(last: usize, paths: &[(Option<usize>, Position)]) -> Vec<Position> {
-    let mut real_path = Vec::new();
-    let mut index = Some(last);
+fn heat_loss(grid: ByteGridView<'_>, pos: Position) -> i64 {
+    (grid[pos.y][pos.x] - b'0') as i64
+}
 
-    while let Some(i) = index {
-        real_path.push(paths[i].1);
-        index = paths[i].0;
+/// Finds the route from the top left to the bottom right block with the least heat loss, for a crucible
+/// that has to move at least `runs.start()` and at most `runs.end()` blocks in a straight line before it
+/// can turn or stop
+pub fn best_route(grid: ByteGridView<'_>, runs: RangeInclusive<usize>) -> Option<Route> {
+    let (min_run, max_run) = runs.into_inner();
+    let start = Position::default();
+    let end = Position {
+        y: grid.rows() - 1,
+        x: grid.columns() - 1,
+    };
+
+    // Instead of moving one block at a time and keeping track of how far the crucible has gone in a
+    // straight line, every move goes all the way to a block where it turns. That means the only state
+    // needed besides the position is whether it moves horizontally or vertically next, so all of the
+    // states fit into dense arrays indexed by `(y * columns + x) * 2 + vertical`.
+    let state =
+        |pos: Position, vertical: bool| (pos.y * grid.columns() + pos.x) * 2 + vertical as usize;
+    let mut costs = vec![i64::MAX; grid.rows() * grid.columns() * 2];
+    let mut parents = vec![None; costs.len()];
+    let mut queue = BinaryHeap::new();
+
+    for vertical in [false, true] {
+        costs[state(start, vertical)] = 0;
+        queue.push(Reverse((0, state(start, vertical))));
     }
 
-    real_path
-}
-
-pub fn part1(input: &str) -> i64 {
-    let grid = ByteGridView::from(input);
-
-    let mut queue = VecDeque::new();
-    queue.push_back(BfsState {
-        pos: Position::default(),
-        dir: Direction::Right,
-        steps_taken: 0,
-        heat_loss: 0,
-        parent_path: None,
-    });
-
-    // (pos, dir, steps_remaining)
-    let mut visited = FxHashMap::<_, i64>::default();
-    let mut path = Vec::new();
-
-    let mut min = i64::MAX;
-
-    while let Some(s) = queue.pop_front() {
-        match visited.entry((s.pos, s.dir, s.steps_taken)) {
-            Entry::Occupied(mut entry) => {
-                if s.heat_loss >= *entry.get() {
-                    // If the current heat loss is greater (or equal), no point in revisiting this.
-                    continue;
-                } else {
-                    entry.insert(s.heat_loss);
-                }
-            }
-            Entry::Vacant(entry) => drop(entry.insert(s.heat_loss)),
+    while let Some(Reverse((cost, index))) = queue.pop() {
+        if cost > costs[index] {
+            continue;
         }
 
-        let path_id = path.len();
-        path.push((s.parent_path, s.pos));
-
-        if s.pos.x == grid.columns() - 1 && s.pos.y == grid.rows() - 1 {
-            min = s.heat_loss.min(min);
-            continue;
+        let pos = Position {
+            y: index / 2 / grid.columns(),
+            x: index / 2 % grid.columns(),
+        };
+        if pos == end {
+            return Some(Route {
+                heat_loss: cost,
+                steps: reconstruct_steps(grid, &parents, index),
+            });
         }
 
-        for (_, y, x) in grid.non_diagonal_neighbors(s.pos.y, s.pos.x) {
-            let direction = if y < s.pos.y {
-                Direction::Up
-            } else if y > s.pos.y {
-                Direction::Down
-            } else if x < s.pos.x {
-                Direction::Left
-            } else if x > s.pos.x {
-                Direction::Right
-            } else {
-                unreachable!()
-            };
-
-            if direction == s.dir.reverse() {
-                continue;
-            }
+        let vertical = index % 2 == 1;
+        let directions = if vertical {
+            [Direction::Up, Direction::Down]
+        } else {
+            [Direction::Left, Direction::Right]
+        };
+
+        for direction in directions {
+            let mut next = pos;
+            let mut next_cost = cost;
+
+            for run in 1..=max_run {
+                match next.checked_offset(direction) {
+                    Some(block) if grid.in_bounds(block) => next = block,
+                    _ => break,
+                }
+                next_cost += heat_loss(grid, next);
 
-            if direction == s.dir && s.pos != (Position { x: 0, y: 0 }) {
-                if s.steps_taken < 3 {
-                    queue.push_back(BfsState {
-                        pos: Position { y, x },
-                        dir: direction,
-                        steps_taken: s.steps_taken + 1,
-                        heat_loss: s.heat_loss + (grid[y][x] - b'0') as i64,
-                        parent_path: Some(path_id),
-                    });
+                let next_index = state(next, !vertical);
+                if run >= min_run && next_cost < costs[next_index] {
+                    costs[next_index] = next_cost;
+                    parents[next_index] = Some(index);
+                    queue.push(Reverse((next_cost, next_index)));
                 }
-            } else {
-                queue.push_back(BfsState {
-                    pos: Position { y, x },
-                    dir: direction,
-                    steps_taken: 1,
-                    heat_loss: s.heat_loss + (grid[y][x] - b'0') as i64,
-                    parent_path: Some(path_id),
-                });
             }
         }
     }
 
-    min
+    None
 }
 
-pub fn part2(input: &str) -> i64 {
-    let grid = ByteGridView::from(input);
-
-    let mut queue = VecDeque::<BfsState>::new();
-    queue.push_back(BfsState {
-        pos: Position { x: 0, y: 0 },
-        dir: Direction::Right,
-        steps_taken: 0,
-        heat_loss: 0,
-        parent_path: None,
-    });
-
-    // (pos, dir, steps_remaining)
-    let mut visited = FxHashMap::<_, i64>::default();
-    let mut path = Vec::new();
-    let mut min = i64::MAX;
-
-    while let Some(s) = queue.pop_front() {
-        match visited.entry((s.pos, s.dir, s.steps_taken)) {
-            Entry::Occupied(mut entry) => {
-                if s.heat_loss >= *entry.get() {
-                    // If the current heat loss is greater (or equal), no point in revisiting this.
-                    continue;
-                } else {
-                    entry.insert(s.heat_loss);
-                }
-            }
-            Entry::Vacant(entry) => drop(entry.insert(s.heat_loss)),
-        }
-
-        let path_id = path.len();
-        path.push((s.parent_path, s.pos));
-
-        if s.pos.x == grid.columns() - 1
-            && s.pos.y == grid.rows() - 1
-            && s.steps_taken >= 4
-            && s.steps_taken <= 10
-        {
-            min = s.heat_loss.min(min);
-            continue;
+/// Follows the parent links from the state at `index` back to the start and fills in the blocks
+/// between two turns
+fn reconstruct_steps(grid: ByteGridView<'_>, parents: &[Option<usize>], index: usize) -> Vec<Step> {
+    let position = |index: usize| Position {
+        y: index / 2 / grid.columns(),
+        x: index / 2 % grid.columns(),
+    };
+
+    let mut turns = vec![position(index)];
+    let mut index = index;
+    while let Some(parent) = parents[index] {
+        turns.push(position(parent));
+        index = parent;
+    }
+    turns.reverse();
+
+    let mut steps = Vec::new();
+    for pair in turns.windows(2) {
+        let (mut pos, to) = (pair[0], pair[1]);
+        let direction = match (to.y.cmp(&pos.y), to.x.cmp(&pos.x)) {
+            (Ordering::Less, _) => Direction::Up,
+            (Ordering::Greater, _) => Direction::Down,
+            (_, Ordering::Less) => Direction::Left,
+            _ => Direction::Right,
+        };
+
+        while pos != to {
+            pos = pos.checked_offset(direction).unwrap();
+            steps.push(Step { pos, direction });
         }
+    }
+    steps
+}
 
-        for (_, y, x) in grid.non_diagonal_neighbors(s.pos.y, s.pos.x) {
-            let direction = if y < s.pos.y {
-                Direction::Up
-            } else if y > s.pos.y {
-                Direction::Down
-            } else if x < s.pos.x {
-                Direction::Left
-            } else if x > s.pos.x {
-                Direction::Right
-            } else {
-                unreachable!()
-            };
-
-            if direction == s.dir.reverse() {
-                continue;
-            }
+/// Draws the route over the heat map, with an arrow on every block showing which way the crucible moved.
+/// With `color`, the route is highlighted using ANSI escape codes.
+pub fn render(grid: ByteGridView<'_>, route: &Route, color: bool) -> String {
+    let arrows = route
+        .steps
+        .iter()
+        .map(|step| (step.pos, step.direction))
+        .collect::<FxHashMap<_, _>>();
 
-            if direction != s.dir && s.pos != (Position { x: 0, y: 0 }) {
-                if s.steps_taken >= 4 {
-                    queue.push_back(BfsState {
-                        pos: Position { y, x },
-                        dir: direction,
-                        steps_taken: 1,
-                        heat_loss: s.heat_loss + (grid[y][x] - b'0') as i64,
-                        parent_path: Some(path_id),
-                    });
+    let mut out = String::new();
+    for y in 0..grid.rows() {
+        for x in 0..grid.columns() {
+            let c = grid[y][x] as char;
+            match arrows.get(&Position { y, x }) {
+                Some(direction) => {
+                    let arrow = match direction {
+                        Direction::Up => '^',
+                        Direction::Down => 'v',
+                        Direction::Left => '<',
+                        Direction::Right => '>',
+                    };
+                    if color {
+                        write!(out, "{}", arrow.red().bold()).unwrap();
+                    } else {
+                        out.push(arrow);
+                    }
                 }
-            } else if s.steps_taken < 10 {
-                queue.push_back(BfsState {
-                    pos: Position { y, x },
-                    dir: direction,
-                    steps_taken: s.steps_taken + 1,
-                    heat_loss: s.heat_loss + (grid[y][x] - b'0') as i64,
-                    parent_path: Some(path_id),
-                });
+                None if color => write!(out, "{}", c.bright_black()).unwrap(),
+                None => out.push(c),
             }
         }
+        out.push('\n');
     }
+    out
+}
 
-    min
+pub fn part1(input: &str) -> i64 {
+    best_route(ByteGridView::from(input), CRUCIBLE)
+        .unwrap()
+        .heat_loss
+}
+
+pub fn part2(input: &str) -> i64 {
+    best_route(ByteGridView::from(input), ULTRA_CRUCIBLE)
+        .unwrap()
+        .heat_loss
 }
 
 #[cfg(test)]
 #[test]
 fn p17t() {
     const INPUT: &str = include_str!("../inputs/day17.txt");
+    const SAMPLE: &str = "2413432311323
+3215453535623
+3255245654254
+3446585845452
+4546657867536
+1438598798454
+4457876987766
+3637877979653
+4654967986887
+4564679986453
+1224686865563
+2546548887735
+4322674655533";
+    assert_eq!(part1(SAMPLE), 102);
+    assert_eq!(part2(SAMPLE), 94);
+    assert_eq!(
+        part2("111111111111\n999999999991\n999999999991\n999999999991\n999999999991"),
+        71
+    );
+
+    // The route has to actually be walkable, and add up to the heat loss
+    let grid = ByteGridView::from(SAMPLE);
+    for (runs, expected) in [(CRUCIBLE, 102), (ULTRA_CRUCIBLE, 94)] {
+        let route = best_route(grid, runs).unwrap();
+        assert_eq!(route.heat_loss, expected);
+        assert_eq!(route.steps.last().unwrap().pos, Position { y: 12, x: 12 });
+        let mut pos = Position::default();
+        for step in &route.steps {
+            pos = pos.checked_offset(step.direction).unwrap();
+            assert_eq!(pos, step.pos);
+        }
+        assert_eq!(
+            route
+                .steps
+                .iter()
+                .map(|step| heat_loss(grid, step.pos))
+                .sum::<i64>(),
+            expected
+        );
+    }
+
+    let route = best_route(grid, CRUCIBLE).unwrap();
+    let plain = render(grid, &route, false);
+    assert_eq!(plain.lines().next(), Some("2>>34^>>>1323"));
+    assert_eq!(plain.lines().count(), 13);
+    assert!(render(grid, &route, true).contains("\x1b["));
     assert_eq!(part1(INPUT.trim()), 1155);
     assert_eq!(part2(INPUT.trim()), 1283);
 }
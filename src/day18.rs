@@ -2,6 +2,8 @@ use itertools::Itertools;
 use owo_colors::OwoColorize;
 use rustc_hash::FxHashSet;
 
+use crate::geometry;
+use crate::geometry::Point;
 use crate::grid::Direction;
 
 #[derive(Default, Clone, Copy, Hash, Debug, PartialEq, Eq)]
@@ -121,22 +123,15 @@ pub fn part1(input: &str) -> i64 {
     (extra + edges.len()) as i64
 }
 
-#[allow(
-    dead_code,
-    reason = "requires some further manual hacking around not in the code to get to the actual answer, TODO fully automate it"
-)]
-pub fn part2(input: &str) -> i64 {
-    let mut edges = Vec::new();
-    let mut x = 0;
-    let mut y = 0;
-
-    let mut min_x = i64::MAX;
-    let mut max_x = i64::MIN;
-    let mut min_y = i64::MAX;
-    let mut max_y = i64::MIN;
+/// Returns how many cubic meters of lava the lagoon dug by following the given moves can hold,
+/// which is the number of tiles within the polygon plus the trench itself
+fn lagoon_size(moves: impl IntoIterator<Item = (Direction, i64)>) -> i64 {
+    geometry::lattice_points(&geometry::polygon_from_moves(Point::default(), moves))
+}
 
-    // find edges
-    for line in input.lines() {
+/// Returns the moves hidden in the colors of the dig plan
+fn hex_moves(input: &str) -> impl Iterator<Item = (Direction, i64)> + '_ {
+    input.lines().map(|line| {
         let hex = line
             .rsplit_once(' ')
             .unwrap()
@@ -152,89 +147,51 @@ pub fn part2(input: &str) -> i64 {
             _ => unreachable!(),
         };
         let count = i64::from_str_radix(&hex[..hex.len() - 1], 16).unwrap();
-        match direction {
-            Direction::Right => {
-                edges.push((x..=x + count, y..=y));
-                x += count;
-            }
-            Direction::Left => {
-                edges.push((x - count..=x, y..=y));
-                x -= count;
-            }
-            Direction::Up => {
-                edges.push((x..=x, y - count..=y));
-                y -= count;
-            }
-            Direction::Down => {
-                edges.push((x..=x, y..=y + count));
-                y += count;
-            }
-        }
-
-        min_x = min_x.min(x);
-        min_y = min_y.min(y);
-        max_x = max_x.max(x);
-        max_y = max_y.max(y);
-    }
-
-    let mut extra = 0;
-
-    let find_edge = |x, y| {
-        edges
-            .iter()
-            .find(|(xr, yr)| xr.contains(&x) && yr.contains(&y))
-    };
-
-    // fill everything within the loop
-    for y in min_y..=max_y {
-        let mut within = false;
-        let mut x = min_x;
-        while x < max_x {
-            if let Some((hx, hy)) = find_edge(x, y) {
-                // Found a hash
-
-                if hx.end() - hx.start() > 0 {
-                    debug_assert!(hy.start() == hy.end());
-                    // More than one hash next to each other, line
-                    let top_left = find_edge(*hx.start(), hy.start() - 1).is_some();
-                    let bottom_left = find_edge(*hx.start(), hy.start() + 1).is_some();
-                    x = *hx.end();
-                    let top_right = find_edge(*hx.end(), hy.start() - 1).is_some();
-                    let bottom_right = find_edge(*hx.end(), hy.start() + 1).is_some();
-
-                    if (top_left && bottom_right) || (top_right && bottom_left) {
-                        within = !within;
-                    } else if (bottom_left && bottom_right) || (top_left && top_right) {
-                        // Nothing.
-                    } else {
-                        // sanity check
-                        unreachable!()
-                    }
-                } else {
-                    within = !within;
-                }
-            } else if let Some((xr, _)) = edges
-                .iter()
-                .find(|(xr, yr)| *xr.start() > x && yr.contains(&y))
-            {
-                if within {
-                    extra += xr.start() - x;
-                }
-                x = *xr.start();
-                continue;
-            } else {
-                break;
-            }
-            x += 1;
-        }
-    }
+        (direction, count)
+    })
+}
 
-    extra + edges.len() as i64
+pub fn part2(input: &str) -> i64 {
+    lagoon_size(hex_moves(input))
 }
 
 #[cfg(test)]
 #[test]
 fn p18t() {
     const INPUT: &str = include_str!("../inputs/day18.txt");
+    const SAMPLE: &str = "R 6 (#70c710)
+D 5 (#0dc571)
+L 2 (#5713f0)
+D 2 (#d2c081)
+R 2 (#59c680)
+D 2 (#411b91)
+L 5 (#8ceee2)
+U 2 (#caa173)
+L 1 (#1b58a2)
+U 2 (#caa171)
+R 2 (#7807d2)
+U 3 (#a77fa3)
+L 2 (#015232)
+U 2 (#7a21e3)";
+
+    // Solving part 1 with the polygon area needs to agree with filling the grid
+    let part1_moves = |input: &'static str| {
+        input.lines().map(|line| {
+            let (direction, count, _) = line.split_ascii_whitespace().collect_tuple().unwrap();
+            let direction = match direction {
+                "R" => Direction::Right,
+                "D" => Direction::Down,
+                "L" => Direction::Left,
+                "U" => Direction::Up,
+                _ => unreachable!(),
+            };
+            (direction, count.parse::<i64>().unwrap())
+        })
+    };
+
+    assert_eq!(part1(SAMPLE), 62);
+    assert_eq!(lagoon_size(part1_moves(SAMPLE)), 62);
     assert_eq!(part1(INPUT.trim()), 61661);
+    assert_eq!(lagoon_size(part1_moves(INPUT.trim())), 61661);
+    assert_eq!(part2(SAMPLE), 952408144115);
 }
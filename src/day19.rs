@@ -2,6 +2,8 @@ use itertools::Itertools;
 use rustc_hash::FxHashMap;
 use std::fmt::Debug;
 
+use crate::ranges::Interval;
+
 #[derive(Debug, Copy, Clone)]
 enum Op {
     Lt,
@@ -19,34 +21,16 @@ enum Workflow<'a> {
     },
 }
 
-#[derive(Copy, Clone)]
-struct IRange<I> {
-    min: I,
-    max: I,
-}
-
-impl IRange<i32> {
-    pub fn values_contained(self) -> i32 {
-        (self.max + 1) - self.min
-    }
-}
-
-impl<I: Debug> Debug for IRange<I> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}..={:?}", self.min, self.max)
-    }
-}
-
 #[derive(Debug, Copy, Clone)]
 struct Costs {
-    x: IRange<i32>,
-    m: IRange<i32>,
-    a: IRange<i32>,
-    s: IRange<i32>,
+    x: Interval,
+    m: Interval,
+    a: Interval,
+    s: Interval,
 }
 
 impl Costs {
-    pub fn range_mut(&mut self, s: &str) -> &mut IRange<i32> {
+    pub fn range_mut(&mut self, s: &str) -> &mut Interval {
         match s {
             "x" => &mut self.x,
             "m" => &mut self.m,
@@ -183,28 +167,22 @@ pub fn part2(input: &str) -> i64 {
                     target,
                 } => {
                     let mut rec_costs = costs;
-                    match op {
-                        Op::Lt => {
-                            // x < 5 -> ZZZ
-                            // checking ZZZ, x: ..=4
-                            // checking others, x: 5..
-                            let range = costs.range_mut(lhs);
-                            range.min = rhs;
-
-                            let rec_range = rec_costs.range_mut(lhs);
-                            rec_range.max = rhs - 1;
-                        }
+                    let range = costs.range_mut(lhs);
+                    let (matching, rest) = match op {
+                        // x < 5 -> ZZZ
+                        // checking ZZZ, x: ..=4
+                        // checking others, x: 5..
+                        Op::Lt => range.split_at(rhs.into()),
+                        // x > 5 -> ZZZ
+                        // checking ZZZ, x: 6..
+                        // checking others, x: ..=5
                         Op::Gt => {
-                            // x > 5 -> ZZZ
-                            // checking ZZZ, x: 6..
-                            // checking others, x: ..=5
-                            let range = costs.range_mut(lhs);
-                            range.max = rhs;
-
-                            let rec_range = rec_costs.range_mut(lhs);
-                            rec_range.min = rhs + 1;
+                            let (rest, matching) = range.split_at(i64::from(rhs) + 1);
+                            (matching, rest)
                         }
-                    }
+                    };
+                    *range = rest;
+                    *rec_costs.range_mut(lhs) = matching;
                     find_accepted_costs(wfmap, target, rec_costs, final_costs, Some(&(wf, parent)));
                 }
             }
@@ -216,21 +194,18 @@ pub fn part2(input: &str) -> i64 {
         &workflow_map,
         "in",
         Costs {
-            x: IRange { min: 1, max: 4000 },
-            m: IRange { min: 1, max: 4000 },
-            a: IRange { min: 1, max: 4000 },
-            s: IRange { min: 1, max: 4000 },
+            x: Interval::from(1..=4000),
+            m: Interval::from(1..=4000),
+            a: Interval::from(1..=4000),
+            s: Interval::from(1..=4000),
         },
         &mut final_costs,
         None,
     );
 
-    final_costs.iter().fold(0, |p, c| {
-        p + (c.x.values_contained() as i64
-            * c.m.values_contained() as i64
-            * c.a.values_contained() as i64
-            * c.s.values_contained() as i64)
-    })
+    final_costs
+        .iter()
+        .fold(0, |p, c| p + c.x.len() * c.m.len() * c.a.len() * c.s.len())
 }
 
 #[cfg(test)]
@@ -1,14 +1,10 @@
-use std::ops::RangeInclusive;
-
 use itertools::Itertools;
 
-#[derive(Debug)]
-struct ConversionSection {
-    source_range: RangeInclusive<i64>,
-    dest_range: RangeInclusive<i64>,
-}
+use crate::ranges::Interval;
+use crate::ranges::PiecewiseMap;
+use crate::ranges::RangeSet;
 
-fn parse_section(s: &str) -> Vec<ConversionSection> {
+fn parse_section(s: &str) -> PiecewiseMap {
     s.lines()
         .skip(1)
         .map(|v| {
@@ -18,23 +14,16 @@ fn parse_section(s: &str) -> Vec<ConversionSection> {
                 .collect_tuple()
                 .unwrap();
 
-            ConversionSection {
-                dest_range: dest_range_start..=dest_range_start + range_len - 1,
-                source_range: source_range_start..=source_range_start + range_len - 1,
-            }
+            (
+                Interval::new(source_range_start, source_range_start + range_len),
+                dest_range_start - source_range_start,
+            )
         })
-        .collect_vec()
+        .collect()
 }
 
-pub fn part1(input: &str) -> i64 {
-    fn find_dest(num: i64, group: &[ConversionSection]) -> i64 {
-        group
-            .iter()
-            .find(|s| s.source_range.contains(&num))
-            .map(|s| num + (s.dest_range.start() - s.source_range.start()))
-            .unwrap_or(num)
-    }
-
+/// Parses the seed numbers and composes all of the maps into a single seed-to-location map
+fn parse(input: &str) -> (Vec<i64>, PiecewiseMap) {
     let mut groups_iter = input.split("\n\n");
 
     let seeds = groups_iter
@@ -45,98 +34,33 @@ pub fn part1(input: &str) -> i64 {
         .map(|v| v.parse::<i64>().unwrap())
         .collect_vec();
 
-    let maps = groups_iter.map(parse_section).next_chunk::<7>().unwrap();
+    let almanac = groups_iter
+        .map(parse_section)
+        .fold(PiecewiseMap::new(), |almanac, map| almanac.then(&map));
+
+    (seeds, almanac)
+}
+
+pub fn part1(input: &str) -> i64 {
+    let (seeds, almanac) = parse(input);
 
     seeds
         .into_iter()
-        .map(|seed| maps.iter().fold(seed, |input, map| find_dest(input, map)))
+        .map(|seed| almanac.apply(seed))
         .min()
         .unwrap()
 }
 
 pub fn part2(input: &str) -> i64 {
-    fn get_outputs(
-        input: RangeInclusive<i64>,
-        map: &[ConversionSection],
-    ) -> Vec<RangeInclusive<i64>> {
-        let mut mapped = vec![];
-        let mut unmapped = vec![];
-
-        for ConversionSection {
-            source_range,
-            dest_range,
-        } in map
-        {
-            let start = (*source_range.start()).max(*input.start());
-            let end = (*source_range.end()).min(*input.end());
-            let shift = dest_range.start() - source_range.start();
-
-            if start < end {
-                mapped.push((start..=end, shift));
-            }
-        }
-        mapped.sort_by(|(r1, _), (r2, _)| r1.start().cmp(r2.start()));
-
-        if let Some((first, _)) = mapped.first() {
-            if first.start() != input.start() {
-                unmapped.push((*input.start())..=(*first.start()));
-            }
-
-            let mut cur = *(first.start());
-            for (m, _) in mapped.iter() {
-                if cur != *m.start() {
-                    unmapped.push(cur..=*m.end());
-                }
-                cur = *m.end() + 1;
-            }
-
-            if cur < *input.end() {
-                unmapped.push(cur..=*input.end());
-            }
-        } else {
-            unmapped.push(*input.start()..=*input.end());
-        }
-
-        unmapped
-            .into_iter()
-            .map(|v| (v, 0))
-            .chain(mapped)
-            .map(|(v, shift)| v.start() + shift..=v.end() + shift)
-            .filter(|v| v.start() < v.end())
-            .sorted_by(|a, b| a.start().cmp(b.start()))
-            .dedup()
-            .collect_vec()
-    }
-
-    let mut groups_iter = input.split("\n\n");
+    let (seeds, almanac) = parse(input);
 
-    let seeds = groups_iter
-        .next()
-        .unwrap()
-        .split(|c| c == ':' || c == ' ')
-        .skip(2)
-        .map(|v| v.parse::<i64>().unwrap())
-        .chunks(2)
+    let seeds = seeds
         .into_iter()
-        .map(|chunks| {
-            let (s, len) = chunks.into_iter().collect_tuple().unwrap();
-            s..=s + len - 1
-        })
-        .collect_vec();
+        .tuples()
+        .map(|(start, len)| Interval::new(start, start + len))
+        .collect::<RangeSet>();
 
-    let maps = groups_iter.map(parse_section).next_chunk::<7>().unwrap();
-
-    maps.iter()
-        .fold(seeds, |v, s| {
-            v.into_iter().fold(Vec::new(), |mut v, input| {
-                v.append(&mut get_outputs(input, s));
-                v
-            })
-        })
-        .into_iter()
-        .map(|v| *v.start())
-        .min()
-        .unwrap()
+    almanac.min_over(&seeds).unwrap()
 }
 
 #[cfg(test)]
@@ -182,4 +106,14 @@ humidity-to-location map:
     assert_eq!(part1(INPUT), 111627841);
     assert_eq!(part2(example), 46);
     assert_eq!(part2(INPUT), 69323688);
+
+    // Seed 82 is the one that ends up at location 46
+    let (_, almanac) = parse(example);
+    assert_eq!(almanac.apply(82), 46);
+    let inverse = almanac.inverse().unwrap();
+    assert_eq!(inverse.apply(46), 82);
+    assert_eq!(
+        almanac.preimage(&Interval::new(46, 47).into()),
+        Interval::new(82, 83).into()
+    );
 }
@@ -1,11 +1,36 @@
-use std::cmp::Ordering;
-
 use aoc::MoreItertools;
 
+use crate::math;
+
 fn race(time: i64, time_to_hold: i64) -> i64 {
     time_to_hold * (time - time_to_hold)
 }
 
+/// Returns the number of ways to hold the button that beat the record.
+///
+/// The distance `h * (time - h)` is a parabola, so the winning hold times are the integers strictly between
+/// the two roots of `h^2 - time * h + record = 0`, i.e. `(time +- sqrt(time^2 - 4 * record)) / 2`.
+fn ways_to_win(time: i64, record: i64) -> i64 {
+    // The distance is largest when holding for half of the time, so if that doesn't win, nothing does
+    if race(time, time / 2) <= record {
+        return 0;
+    }
+
+    // The integer square root rounds down, so this can be off by one in either direction
+    let discriminant = time * time - 4 * record;
+    let mut first = (time - math::isqrt(discriminant)) / 2;
+    while race(time, first) <= record {
+        first += 1;
+    }
+    while first > 0 && race(time, first - 1) > record {
+        first -= 1;
+    }
+
+    // The parabola is symmetric, so the last winning hold time is as far from `time` as the first one is from 0
+    let last = time - first;
+    last - first + 1
+}
+
 pub fn part1(input: &str) -> i64 {
     let mut lines = input.lines();
     let times = lines
@@ -23,16 +48,8 @@ pub fn part1(input: &str) -> i64 {
         .map(|v| v.parse::<i64>().unwrap());
 
     times
-        .into_iter()
         .zip(distance)
-        .map(|(time, record)| {
-            i64::try_from(
-                (0..=time)
-                    .filter(|&time_to_hold| race(time, time_to_hold) > record)
-                    .count(),
-            )
-            .unwrap()
-        })
+        .map(|(time, record)| ways_to_win(time, record))
         .product()
 }
 
@@ -54,19 +71,7 @@ pub fn part2(input: &str) -> i64 {
         .filter(|b| !b.is_ascii_whitespace())
         .parse_int();
 
-    // Find the point at which our distance becomes >= record
-    let smallest_time_pos = (0..)
-        .position(|time_to_hold| race(time, time_to_hold) > record)
-        .unwrap() as i64;
-
-    // Everything after the point at which the distance goes down again is "mirrored".
-    // The point at which the distance goes down is always the middle.
-    // So, the count is `(smallest point where distance >= record..=middle) * 2`
-    match race(time, time / 2).cmp(&race(time, time / 2 + 1)) {
-        Ordering::Greater => ((time / 2) - smallest_time_pos + 1) * 2 - 1,
-        Ordering::Equal => ((time / 2) - smallest_time_pos + 1) * 2,
-        Ordering::Less => unreachable!(),
-    }
+    ways_to_win(time, record)
 }
 
 #[cfg(test)]
@@ -81,4 +86,27 @@ Distance:  9  40  200"
     assert_eq!(part1(INPUT), 128700);
     assert_eq!(part2(example), 71503);
     assert_eq!(part2(INPUT), 39594072);
+
+    let brute_force = |time, record| {
+        (0..=time)
+            .filter(|&time_to_hold| race(time, time_to_hold) > record)
+            .count() as i64
+    };
+    // xorshift, so that the test is reproducible without pulling in a crate for it
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+    let mut random = |bound: u64| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        (state % bound) as i64
+    };
+    for _ in 0..2000 {
+        let time = random(200);
+        // Mostly records close to the best possible distance, where the boundaries are tricky
+        let best = race(time, time / 2);
+        let record = (best - random(20)).max(0);
+        assert_eq!(ways_to_win(time, record), brute_force(time, record));
+        let record = random(best as u64 + 2);
+        assert_eq!(ways_to_win(time, record), brute_force(time, record));
+    }
 }
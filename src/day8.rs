@@ -1,8 +1,10 @@
-use std::collections::HashMap;
-
-use num::Integer;
+use itertools::Itertools;
 use rustc_hash::FxHashMap;
 
+use crate::cycle;
+use crate::cycle::Cycle;
+use crate::math;
+
 pub fn part1(input: &str) -> i64 {
     let mut iter = input.split("\n\n");
     let steps = iter.next().unwrap().bytes().cycle();
@@ -41,9 +43,22 @@ pub fn part1(input: &str) -> i64 {
     count
 }
 
+/// The steps at which a ghost is on a node ending with Z
+struct ZHits {
+    cycle: Cycle,
+    /// All such steps before the ghost's path starts repeating
+    steps: Vec<usize>,
+}
+
+impl ZHits {
+    fn contains(&self, step: usize) -> bool {
+        self.steps.binary_search(&self.cycle.reduce(step)).is_ok()
+    }
+}
+
 pub fn part2(input: &str) -> i64 {
     let mut iter = input.split("\n\n");
-    let steps = iter.next().unwrap().bytes();
+    let steps = iter.next().unwrap().as_bytes();
     let nodes = iter
         .next()
         .unwrap()
@@ -58,34 +73,79 @@ pub fn part2(input: &str) -> i64 {
                 .unwrap();
             (name, instrs)
         })
-        .collect::<HashMap<_, _>>();
-
-    let paths = nodes.keys().copied().filter(|v| v.ends_with('A'));
+        .collect::<FxHashMap<_, _>>();
 
-    let mut cycle_counts = Vec::new();
-    let mut seen = FxHashMap::default();
-    for path in paths {
-        let mut node = path;
+    // A ghost's path only depends on the node it's on and where it is in the instructions,
+    // so it has to start repeating at some point
+    let ghosts = nodes
+        .keys()
+        .copied()
+        .filter(|v| v.ends_with('A'))
+        .map(|start| {
+            let (cycle, states) = cycle::detect((start, 0), |&(node, index)| {
+                let node = match steps[index] {
+                    b'L' => nodes[node].0,
+                    b'R' => nodes[node].1,
+                    _ => unreachable!(),
+                };
+                (node, (index + 1) % steps.len())
+            });
 
-        for (node_cycle_count, (index, step)) in steps.clone().enumerate().cycle().enumerate() {
-            if let Some(index) = seen.get(&(index, step, node)) {
-                cycle_counts.push((node_cycle_count - index) as i64);
-                break;
-            } else {
-                seen.insert((index, step, node), node_cycle_count);
+            ZHits {
+                cycle,
+                steps: states
+                    .iter()
+                    .positions(|(node, _)| node.ends_with('Z'))
+                    .collect_vec(),
             }
+        })
+        .collect_vec();
 
-            match step {
-                b'L' => node = nodes[&node].0,
-                b'R' => node = nodes[&node].1,
-                _ => unreachable!(),
-            }
-        }
+    // Until every ghost is in its cycle, all we can do is check step by step
+    let settled = ghosts.iter().map(|g| g.cycle.prefix).max().unwrap();
+    if let Some(step) = (0..settled).find(|&step| ghosts.iter().all(|g| g.contains(step))) {
+        return step as i64;
+    }
+
+    // After that, every ghost is on a Z node at some fixed offsets into its cycle: (offset, period)
+    let offsets = ghosts
+        .iter()
+        .map(|g| {
+            g.steps
+                .iter()
+                .filter(|&&step| step >= g.cycle.prefix)
+                .map(|&step| (step as i64, g.cycle.period as i64))
+                .collect_vec()
+        })
+        .collect_vec();
 
-        seen.clear();
+    // What the puzzle inputs are made for: every ghost is on a Z node exactly every `period` steps
+    if let Some(lcm) = offsets
+        .iter()
+        .map(|o| match *o.as_slice() {
+            [(offset, period)] if offset % period == 0 => Some(period),
+            _ => None,
+        })
+        .reduce(|a, b| Some(math::lcm(a?, b?)))
+        .flatten()
+        && lcm >= settled as i64
+    {
+        return lcm;
     }
 
-    cycle_counts.into_iter().reduce(|x, y| x.lcm(&y)).unwrap()
+    // Otherwise, try every combination of offsets and find the first step that satisfies all of them
+    offsets
+        .iter()
+        .map(|o| o.iter())
+        .multi_cartesian_product()
+        .filter_map(|combination| math::crt(combination.into_iter().copied()))
+        .map(|(a, m)| {
+            // The first step at or after `settled` that satisfies `step = a (mod m)`
+            let missing = (settled as i64 - a).max(0);
+            a + (missing + m - 1) / m * m
+        })
+        .min()
+        .expect("ghosts are never on Z nodes at the same time")
 }
 
 #[cfg(test)]
@@ -93,5 +153,35 @@ pub fn part2(input: &str) -> i64 {
 fn p8t() {
     const INPUT: &str = include_str!("../inputs/day8.txt");
     assert_eq!(part1(INPUT), 21251);
+    assert_eq!(
+        part2(
+            "LR
+
+11A = (11B, XXX)
+11B = (XXX, 11Z)
+11Z = (11B, XXX)
+22A = (22B, XXX)
+22B = (22C, 22C)
+22C = (22Z, 22Z)
+22Z = (22B, 22B)
+XXX = (XXX, XXX)"
+        ),
+        6
+    );
+    // Z nodes are reached at steps 2, 4, 6, ... and 1, 4, 7, ..., so the LCM of the periods is wrong
+    assert_eq!(
+        part2(
+            "L
+
+11A = (11B, 11B)
+11B = (11Z, 11Z)
+11Z = (11B, 11B)
+22A = (22Z, 22Z)
+22Z = (22B, 22B)
+22B = (22C, 22C)
+22C = (22Z, 22Z)"
+        ),
+        4
+    );
     assert_eq!(part2(INPUT), 11678319315857);
 }
@@ -0,0 +1,230 @@
+//! Flood fill and connected components on anything implementing [`Grid`].
+//!
+//! Which tiles can be walked on is decided by a `passable` function that gets both the position and
+//! the byte at that position, and only orthogonal neighbors are considered connected.
+
+use rustc_hash::FxHashSet;
+
+use crate::grid::ByteGrid;
+use crate::grid::Direction;
+use crate::grid::Grid;
+use crate::grid::Position;
+use crate::search;
+
+/// Returns all positions reachable from `seed` without crossing a tile that isn't passable.
+/// The seed itself is always included.
+#[allow(
+    dead_code,
+    reason = "day 10 counts enclosed tiles with Pick's theorem instead"
+)]
+pub fn flood_fill(
+    grid: &impl Grid,
+    seed: Position,
+    passable: impl Fn(Position, u8) -> bool,
+) -> FxHashSet<Position> {
+    search::reachable([seed], |&pos| {
+        grid.orthogonal_neighbors_with_direction(pos)
+            .filter(|&(c, next, _)| passable(next, c))
+            .map(|(_, next, _)| next)
+            .collect::<Vec<_>>()
+    })
+}
+
+/// The connected components (regions) of the passable tiles of a grid
+#[derive(Debug, Clone)]
+#[allow(dead_code, reason = "no solution looks for regions yet")]
+pub struct Components {
+    /// The component of every tile, in row-major order. `None` for tiles that aren't passable.
+    labels: Vec<Option<usize>>,
+    columns: usize,
+    sizes: Vec<usize>,
+    touches_border: Vec<bool>,
+}
+
+#[allow(dead_code, reason = "no solution looks for regions yet")]
+impl Components {
+    /// Returns the number of components
+    pub fn count(&self) -> usize {
+        self.sizes.len()
+    }
+
+    /// Returns the component that a tile belongs to, or `None` if it isn't passable.
+    /// Components are numbered from `0` in the order in which they're first seen going row by row.
+    pub fn label(&self, pos: Position) -> Option<usize> {
+        self.labels[pos.y * self.columns + pos.x]
+    }
+
+    /// Returns the number of tiles in a component
+    pub fn size(&self, label: usize) -> usize {
+        self.sizes[label]
+    }
+
+    /// Whether any of the tiles of a component lie on the edge of the grid
+    pub fn touches_border(&self, label: usize) -> bool {
+        self.touches_border[label]
+    }
+
+    /// Returns the components that are completely enclosed, i.e. don't touch the border of the grid
+    pub fn enclosed(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.count()).filter(|&label| !self.touches_border(label))
+    }
+}
+
+/// Splits the passable tiles of a grid into connected components
+#[allow(dead_code, reason = "no solution looks for regions yet")]
+pub fn connected_components(
+    grid: &impl Grid,
+    passable: impl Fn(Position, u8) -> bool,
+) -> Components {
+    let mut components = Components {
+        labels: vec![None; grid.rows() * grid.columns()],
+        columns: grid.columns(),
+        sizes: Vec::new(),
+        touches_border: Vec::new(),
+    };
+    let mut stack = Vec::new();
+
+    for y in 0..grid.rows() {
+        for x in 0..grid.columns() {
+            let seed = Position { y, x };
+            if components.label(seed).is_some() || !passable(seed, grid.get(y, x)) {
+                continue;
+            }
+
+            let label = components.count();
+            let mut size = 0;
+            let mut touches_border = false;
+            components.labels[y * grid.columns() + x] = Some(label);
+            stack.push(seed);
+
+            while let Some(pos) = stack.pop() {
+                size += 1;
+                touches_border |= pos.y == 0
+                    || pos.x == 0
+                    || pos.y == grid.rows() - 1
+                    || pos.x == grid.columns() - 1;
+
+                for (c, next, _) in grid.orthogonal_neighbors_with_direction(pos) {
+                    let index = next.y * grid.columns() + next.x;
+                    if components.labels[index].is_none() && passable(next, c) {
+                        components.labels[index] = Some(label);
+                        stack.push(next);
+                    }
+                }
+            }
+
+            components.sizes.push(size);
+            components.touches_border.push(touches_border);
+        }
+    }
+
+    components
+}
+
+/// Spreads the tiles of a grid apart so that there's a gap between any two neighboring tiles, which
+/// makes it possible to flood fill "between" tiles (e.g. squeezing between two pipes).
+///
+/// The tile at `(y, x)` ends up at `(2y, 2x)`. The gap between a tile and its right or bottom neighbor
+/// is filled with whatever `join` returns given both tiles and the direction from the first to the
+/// second tile, or `gap` if that returns `None`. The remaining gaps are filled with `gap`, too.
+#[allow(
+    dead_code,
+    reason = "day 10 counts enclosed tiles with Pick's theorem instead"
+)]
+pub fn expand2x(
+    grid: &impl Grid,
+    gap: u8,
+    join: impl Fn(u8, Direction, u8) -> Option<u8>,
+) -> ByteGrid {
+    let mut expanded = ByteGrid::new(grid.rows() * 2 - 1, grid.columns() * 2 - 1, gap);
+
+    for y in 0..grid.rows() {
+        for x in 0..grid.columns() {
+            let c = grid.get(y, x);
+            expanded[y * 2][x * 2] = c;
+
+            if x + 1 < grid.columns()
+                && let Some(joined) = join(c, Direction::Right, grid.get(y, x + 1))
+            {
+                expanded[y * 2][x * 2 + 1] = joined;
+            }
+
+            if y + 1 < grid.rows()
+                && let Some(joined) = join(c, Direction::Down, grid.get(y + 1, x))
+            {
+                expanded[y * 2 + 1][x * 2] = joined;
+            }
+        }
+    }
+
+    expanded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::ByteGridView;
+
+    #[test]
+    fn test_flood() {
+        const MAZE: &str = "..........
+.F------7.
+.|F----7|.
+.||....||.
+.||....||.
+.|L-7F-J|.
+.|..||..|.
+.L--JL--J.
+..........";
+        let view = ByteGridView::from(MAZE);
+        let owned = ByteGrid::from(view);
+        assert_eq!(owned.to_string(), MAZE);
+
+        let empty = |_, c| c == b'.';
+        assert_eq!(flood_fill(&view, Position { y: 0, x: 0 }, empty).len(), 34);
+        assert_eq!(flood_fill(&owned, Position { y: 6, x: 2 }, empty).len(), 2);
+
+        // Without squeezing between pipes, the middle area looks enclosed even though it isn't
+        let components = connected_components(&owned, empty);
+        assert_eq!(components.count(), 4);
+        assert_eq!(components.enclosed().count(), 3);
+        assert_eq!(components.label(Position { y: 3, x: 3 }), Some(1));
+        assert_eq!(components.label(Position { y: 1, x: 1 }), None);
+        assert_eq!(components.size(1), 8);
+
+        let edges = |c: u8| -> &'static [Direction] {
+            match c {
+                b'|' => &[Direction::Up, Direction::Down],
+                b'-' => &[Direction::Left, Direction::Right],
+                b'L' => &[Direction::Up, Direction::Right],
+                b'J' => &[Direction::Up, Direction::Left],
+                b'7' => &[Direction::Down, Direction::Left],
+                b'F' => &[Direction::Down, Direction::Right],
+                _ => &[],
+            }
+        };
+        let expanded = expand2x(&view, b'.', |a, dir, b| {
+            (edges(a).contains(&dir) && edges(b).contains(&dir.reverse())).then_some(match dir {
+                Direction::Right => b'-',
+                _ => b'|',
+            })
+        });
+        assert_eq!(expanded.rows(), 17);
+        assert_eq!(expanded.columns(), 19);
+        assert_eq!(&expanded[2][..7], b"..F----");
+        assert_eq!(&expanded[3][..7], b"..|....");
+
+        let components = connected_components(&expanded, empty);
+        let inside = components
+            .enclosed()
+            .map(|label| {
+                (0..view.rows())
+                    .flat_map(|y| (0..view.columns()).map(move |x| Position { y: y * 2, x: x * 2 }))
+                    .filter(|&pos| components.label(pos) == Some(label))
+                    .count()
+            })
+            .sum::<usize>();
+        assert_eq!(components.enclosed().count(), 1);
+        assert_eq!(inside, 4);
+    }
+}
@@ -0,0 +1,139 @@
+//! Area and lattice point counts of simple polygons whose vertices lie on integer coordinates.
+//!
+//! Polygons are given as their vertices in order (clockwise or counterclockwise). The last vertex is
+//! implicitly connected to the first one, but repeating the first vertex at the end is fine, too.
+
+use crate::grid::Direction;
+use crate::grid::Offset;
+use crate::grid::Position;
+use crate::math::gcd;
+
+#[derive(Default, Clone, Copy, Hash, Debug, PartialEq, Eq)]
+pub struct Point {
+    pub y: i64,
+    pub x: i64,
+}
+
+impl From<Position> for Point {
+    fn from(pos: Position) -> Self {
+        Self {
+            y: pos.y as i64,
+            x: pos.x as i64,
+        }
+    }
+}
+
+fn edges(vertices: &[Point]) -> impl Iterator<Item = (Point, Point)> + '_ {
+    vertices
+        .iter()
+        .copied()
+        .zip(vertices.iter().copied().cycle().skip(1))
+}
+
+/// Returns the vertices of the polygon traced by starting at `start` and moving the given
+/// number of steps in each direction
+pub fn polygon_from_moves(
+    start: Point,
+    moves: impl IntoIterator<Item = (Direction, i64)>,
+) -> Vec<Point> {
+    let mut vertices = vec![start];
+    let mut cur = start;
+
+    for (direction, count) in moves {
+        let (dy, dx) = direction.offset();
+        cur = Point {
+            y: cur.y + dy as i64 * count,
+            x: cur.x + dx as i64 * count,
+        };
+        vertices.push(cur);
+    }
+
+    vertices
+}
+
+/// Returns twice the area of the polygon, using the shoelace formula.
+/// Twice the area is always an integer, while the area itself might end in `.5`.
+pub fn double_area(vertices: &[Point]) -> i64 {
+    edges(vertices)
+        .map(|(a, b)| a.x * b.y - b.x * a.y)
+        .sum::<i64>()
+        .abs()
+}
+
+/// Returns the number of integer points on the boundary of the polygon
+pub fn boundary_points(vertices: &[Point]) -> i64 {
+    edges(vertices)
+        .map(|(a, b)| gcd((b.x - a.x).abs(), (b.y - a.y).abs()))
+        .sum()
+}
+
+/// Returns the number of integer points strictly inside the polygon, using Pick's theorem:
+/// `A = i + b/2 - 1`
+pub fn interior_points(vertices: &[Point]) -> i64 {
+    (double_area(vertices) - boundary_points(vertices) + 2) / 2
+}
+
+/// Returns the number of integer points inside of or on the boundary of the polygon.
+/// If every point is a tile (like in a grid), this is the number of tiles covered by the polygon.
+pub fn lattice_points(vertices: &[Point]) -> i64 {
+    interior_points(vertices) + boundary_points(vertices)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_geometry() {
+        let square = [
+            Point { y: 0, x: 0 },
+            Point { y: 0, x: 4 },
+            Point { y: 4, x: 4 },
+            Point { y: 4, x: 0 },
+        ];
+        assert_eq!(double_area(&square), 32);
+        assert_eq!(boundary_points(&square), 16);
+        assert_eq!(interior_points(&square), 9);
+        assert_eq!(lattice_points(&square), 25);
+
+        // Orientation and a repeated start vertex don't matter
+        let mut reversed = square.to_vec();
+        reversed.reverse();
+        reversed.push(reversed[0]);
+        assert_eq!(double_area(&reversed), 32);
+        assert_eq!(lattice_points(&reversed), 25);
+
+        let triangle = [
+            Point { y: 0, x: 0 },
+            Point { y: 2, x: 3 },
+            Point { y: 0, x: 3 },
+        ];
+        assert_eq!(double_area(&triangle), 6);
+        assert_eq!(boundary_points(&triangle), 3 + 2 + 1);
+        assert_eq!(interior_points(&triangle), 1);
+
+        // The lagoon from the day 18 example
+        let lagoon = polygon_from_moves(
+            Point::default(),
+            [
+                (Direction::Right, 6),
+                (Direction::Down, 5),
+                (Direction::Left, 2),
+                (Direction::Down, 2),
+                (Direction::Right, 2),
+                (Direction::Down, 2),
+                (Direction::Left, 5),
+                (Direction::Up, 2),
+                (Direction::Left, 1),
+                (Direction::Up, 2),
+                (Direction::Right, 2),
+                (Direction::Up, 3),
+                (Direction::Left, 2),
+                (Direction::Up, 2),
+            ],
+        );
+        assert_eq!(lagoon.first(), lagoon.last());
+        assert_eq!(boundary_points(&lagoon), 38);
+        assert_eq!(lattice_points(&lagoon), 62);
+    }
+}
@@ -1,4 +1,6 @@
+use std::fmt;
 use std::ops::Index;
+use std::ops::IndexMut;
 
 #[derive(Default, Clone, Copy, Hash, Debug, PartialEq, Eq)]
 pub struct Position {
@@ -7,29 +9,53 @@ pub struct Position {
 }
 
 impl Position {
-    pub fn right(self) -> Self {
-        Self {
-            y: self.y,
-            x: self.x + 1,
-        }
+    /// Moves by the given offset, or returns `None` if that would leave the grid at the top or left
+    pub fn checked_offset(self, offset: impl Offset) -> Option<Self> {
+        let (dy, dx) = offset.offset();
+        Some(Self {
+            y: self.y.checked_add_signed(dy)?,
+            x: self.x.checked_add_signed(dx)?,
+        })
     }
-    pub fn down(self) -> Self {
+}
+
+/// A position that is allowed to be negative, e.g. for positions in a [`WrappingGridView`]
+#[derive(Default, Clone, Copy, Hash, Debug, PartialEq, Eq)]
+#[allow(dead_code, reason = "only used by WrappingGridView")]
+pub struct SignedPosition {
+    pub y: isize,
+    pub x: isize,
+}
+
+#[allow(dead_code, reason = "only used by WrappingGridView")]
+impl SignedPosition {
+    pub fn offset(self, offset: impl Offset) -> Self {
+        let (dy, dx) = offset.offset();
         Self {
-            y: self.y + 1,
-            x: self.x,
+            y: self.y + dy,
+            x: self.x + dx,
         }
     }
-    pub fn left(self) -> Self {
+}
+
+impl From<Position> for SignedPosition {
+    fn from(pos: Position) -> Self {
         Self {
-            y: self.y,
-            x: self.x - 1,
+            y: pos.y as isize,
+            x: pos.x as isize,
         }
     }
-    pub fn up(self) -> Self {
-        Self {
-            y: self.y - 1,
-            x: self.x,
-        }
+}
+
+/// A relative movement on a grid
+pub trait Offset: Copy {
+    /// Returns the offset as `(dy, dx)`
+    fn offset(self) -> (isize, isize);
+}
+
+impl Offset for (isize, isize) {
+    fn offset(self) -> (isize, isize) {
+        self
     }
 }
 
@@ -42,6 +68,8 @@ pub enum Direction {
 }
 
 impl Direction {
+    pub const ALL: [Self; 4] = [Self::Up, Self::Right, Self::Down, Self::Left];
+
     pub fn reverse(self) -> Self {
         match self {
             Direction::Up => Self::Down,
@@ -52,12 +80,70 @@ impl Direction {
     }
 }
 
+impl Offset for Direction {
+    fn offset(self) -> (isize, isize) {
+        match self {
+            Direction::Up => (-1, 0),
+            Direction::Down => (1, 0),
+            Direction::Left => (0, -1),
+            Direction::Right => (0, 1),
+        }
+    }
+}
+
+/// Like [`Direction`], but also including the four diagonals
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
-pub struct Directions {
-    left: bool,
-    right: bool,
-    up: bool,
-    down: bool,
+#[allow(dead_code, reason = "no solution looks at diagonal neighbors yet")]
+pub enum Compass {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+#[allow(dead_code, reason = "no solution looks at diagonal neighbors yet")]
+impl Compass {
+    /// All eight directions, clockwise starting at north
+    pub const ALL: [Self; 8] = [
+        Self::North,
+        Self::NorthEast,
+        Self::East,
+        Self::SouthEast,
+        Self::South,
+        Self::SouthWest,
+        Self::West,
+        Self::NorthWest,
+    ];
+}
+
+impl Offset for Compass {
+    fn offset(self) -> (isize, isize) {
+        match self {
+            Compass::North => (-1, 0),
+            Compass::NorthEast => (-1, 1),
+            Compass::East => (0, 1),
+            Compass::SouthEast => (1, 1),
+            Compass::South => (1, 0),
+            Compass::SouthWest => (1, -1),
+            Compass::West => (0, -1),
+            Compass::NorthWest => (-1, -1),
+        }
+    }
+}
+
+impl From<Direction> for Compass {
+    fn from(dir: Direction) -> Self {
+        match dir {
+            Direction::Up => Self::North,
+            Direction::Down => Self::South,
+            Direction::Left => Self::West,
+            Direction::Right => Self::East,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -92,24 +178,40 @@ impl<'a> ByteGridView<'a> {
         self.rows
     }
 
-    pub fn is_right_edge(&self, c: usize) -> bool {
-        self.columns() - 1 == c
+    pub fn columns(&self) -> usize {
+        self.columns
     }
 
-    pub fn is_left_edge(&self, c: usize) -> bool {
-        0 == c
+    /// Returns an iterator over the bytes of a column, top to bottom.
+    /// Unlike rows, columns aren't contiguous, so this steps over the underlying buffer instead.
+    #[allow(dead_code, reason = "no solution reads whole columns yet")]
+    pub fn column(
+        &self,
+        col: usize,
+    ) -> impl DoubleEndedIterator<Item = u8> + ExactSizeIterator + 'a {
+        assert!(col < self.columns, "column {col} out of bounds");
+        self.store[col..].iter().step_by(self.columns + 1).copied()
     }
 
-    pub fn is_top_edge(&self, r: usize) -> bool {
-        r == 0
+    /// Returns this grid as a [`TransformedGridView`] that is neither rotated nor flipped.
+    pub fn view(self) -> TransformedGridView<'a> {
+        TransformedGridView {
+            grid: self,
+            transposed: false,
+            flip_rows: false,
+            flip_columns: false,
+        }
     }
 
-    pub fn is_bottom_edge(&self, r: usize) -> bool {
-        self.rows() - 1 == r
+    /// Rows become columns and columns become rows.
+    pub fn transpose(self) -> TransformedGridView<'a> {
+        self.view().transpose()
     }
 
-    pub fn columns(&self) -> usize {
-        self.columns
+    /// Returns a view in which this grid is repeated infinitely in every direction
+    #[allow(dead_code, reason = "no puzzle has had an infinite grid yet")]
+    pub fn wrapping(self) -> WrappingGridView<'a> {
+        WrappingGridView { grid: self }
     }
 
     /// Normalize to a row index
@@ -178,29 +280,28 @@ impl<'a> ByteGridView<'a> {
             }
         })
     }
+}
 
-    pub fn non_diagonal_neighbors(
-        &self,
-        row: usize,
-        col: usize,
-    ) -> impl Iterator<Item = (u8, usize, usize)> + '_ {
-        std::iter::from_coroutine(move || {
-            if let Some(left_column) = col.checked_sub(1) {
-                yield (self[row][left_column], row, left_column);
-            }
+#[allow(dead_code, reason = "day 13 only needs the transpose")]
+impl<'a> ByteGridView<'a> {
+    pub fn rotate_cw(self) -> TransformedGridView<'a> {
+        self.view().rotate_cw()
+    }
 
-            if col != self.columns() - 1 {
-                yield (self[row][col + 1], row, col + 1);
-            }
+    pub fn rotate_ccw(self) -> TransformedGridView<'a> {
+        self.view().rotate_ccw()
+    }
 
-            if let Some(top_row) = row.checked_sub(1) {
-                yield (self[top_row][col], top_row, col);
-            }
+    pub fn rotate_180(self) -> TransformedGridView<'a> {
+        self.view().rotate_180()
+    }
 
-            if row != self.rows() - 1 {
-                yield (self[row + 1][col], row + 1, col);
-            }
-        })
+    pub fn flip_horizontal(self) -> TransformedGridView<'a> {
+        self.view().flip_horizontal()
+    }
+
+    pub fn flip_vertical(self) -> TransformedGridView<'a> {
+        self.view().flip_vertical()
     }
 }
 
@@ -214,6 +315,350 @@ impl<'a> Index<usize> for ByteGridView<'a> {
     }
 }
 
+/// Read-only access to a rectangular grid of bytes.
+///
+/// This lets algorithms that work on rows be reused for columns by passing them a transposed view
+/// instead of the grid itself.
+pub trait Grid {
+    fn rows(&self) -> usize;
+    fn columns(&self) -> usize;
+    #[allow(dead_code, reason = "solutions index the concrete grid types directly")]
+    fn get(&self, row: usize, col: usize) -> u8;
+
+    #[allow(dead_code, reason = "solutions slice rows out directly")]
+    fn row(&self, row: usize) -> impl DoubleEndedIterator<Item = u8> + ExactSizeIterator + '_ {
+        (0..self.columns()).map(move |col| self.get(row, col))
+    }
+
+    #[allow(dead_code, reason = "no solution reads whole columns yet")]
+    fn column(&self, col: usize) -> impl DoubleEndedIterator<Item = u8> + ExactSizeIterator + '_ {
+        (0..self.rows()).map(move |row| self.get(row, col))
+    }
+
+    fn in_bounds(&self, pos: Position) -> bool {
+        pos.y < self.rows() && pos.x < self.columns()
+    }
+
+    /// Returns the neighbors of `pos` at the given offsets, skipping any that would be out of bounds.
+    /// The offset that led to a neighbor is yielded alongside it, so this can be used with
+    /// [`Direction::ALL`], [`Compass::ALL`] or any custom stencil of `(dy, dx)` pairs.
+    #[allow(dead_code, reason = "only the flood fill walks neighbors")]
+    fn neighbors_by<O: Offset>(
+        &self,
+        pos: Position,
+        offsets: impl IntoIterator<Item = O>,
+    ) -> impl Iterator<Item = (u8, Position, O)> {
+        offsets.into_iter().filter_map(move |offset| {
+            let neighbor = pos.checked_offset(offset)?;
+            self.in_bounds(neighbor)
+                .then(|| (self.get(neighbor.y, neighbor.x), neighbor, offset))
+        })
+    }
+
+    /// Returns all eight neighbors of `pos` (including diagonals) that are within bounds
+    #[allow(dead_code, reason = "no solution looks at diagonal neighbors yet")]
+    fn all_neighbors(&self, pos: Position) -> impl Iterator<Item = (u8, Position)> {
+        self.neighbors_by(pos, Compass::ALL)
+            .map(|(c, neighbor, _)| (c, neighbor))
+    }
+
+    #[allow(dead_code, reason = "only the flood fill walks neighbors")]
+    fn orthogonal_neighbors_with_direction(
+        &self,
+        pos: Position,
+    ) -> impl Iterator<Item = (u8, Position, Direction)> {
+        self.neighbors_by(pos, Direction::ALL)
+    }
+}
+
+impl Grid for ByteGridView<'_> {
+    fn rows(&self) -> usize {
+        self.rows
+    }
+
+    fn columns(&self) -> usize {
+        self.columns
+    }
+
+    fn get(&self, row: usize, col: usize) -> u8 {
+        self[row][col]
+    }
+
+    fn row(&self, row: usize) -> impl DoubleEndedIterator<Item = u8> + ExactSizeIterator + '_ {
+        self[row].iter().copied()
+    }
+
+    fn column(&self, col: usize) -> impl DoubleEndedIterator<Item = u8> + ExactSizeIterator + '_ {
+        ByteGridView::column(self, col)
+    }
+}
+
+/// An owned and mutable grid of bytes.
+/// Unlike [`ByteGridView`], rows aren't separated by newlines in the underlying buffer.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[allow(dead_code, reason = "no solution builds a grid of its own yet")]
+pub struct ByteGrid {
+    store: Vec<u8>,
+    rows: usize,
+    columns: usize,
+}
+
+#[allow(dead_code, reason = "no solution builds a grid of its own yet")]
+impl ByteGrid {
+    pub fn new(rows: usize, columns: usize, fill: u8) -> Self {
+        Self {
+            store: vec![fill; rows * columns],
+            rows,
+            columns,
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn columns(&self) -> usize {
+        self.columns
+    }
+}
+
+impl<'a> From<ByteGridView<'a>> for ByteGrid {
+    fn from(grid: ByteGridView<'a>) -> Self {
+        Self {
+            store: grid.store.iter().copied().filter(|&c| c != b'\n').collect(),
+            rows: grid.rows(),
+            columns: grid.columns(),
+        }
+    }
+}
+
+impl Index<usize> for ByteGrid {
+    type Output = [u8];
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.store[index * self.columns..][..self.columns]
+    }
+}
+
+impl IndexMut<usize> for ByteGrid {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.store[index * self.columns..][..self.columns]
+    }
+}
+
+impl Grid for ByteGrid {
+    fn rows(&self) -> usize {
+        self.rows
+    }
+
+    fn columns(&self) -> usize {
+        self.columns
+    }
+
+    fn get(&self, row: usize, col: usize) -> u8 {
+        self[row][col]
+    }
+
+    fn row(&self, row: usize) -> impl DoubleEndedIterator<Item = u8> + ExactSizeIterator + '_ {
+        self[row].iter().copied()
+    }
+}
+
+impl fmt::Display for ByteGrid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in 0..self.rows {
+            if row != 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", String::from_utf8_lossy(&self[row]))?;
+        }
+        Ok(())
+    }
+}
+
+/// A transposed, rotated and/or flipped view into a [`ByteGridView`]. Nothing is copied,
+/// coordinates are instead mapped back to the underlying grid on every access.
+#[derive(Debug, Clone, Copy)]
+pub struct TransformedGridView<'a> {
+    grid: ByteGridView<'a>,
+    /// Whether rows and columns of the underlying grid are swapped.
+    /// This is applied *after* flipping.
+    transposed: bool,
+    /// Whether the row order (in terms of this view) is reversed
+    flip_rows: bool,
+    /// Whether the column order (in terms of this view) is reversed
+    flip_columns: bool,
+}
+
+impl<'a> TransformedGridView<'a> {
+    pub fn rows(&self) -> usize {
+        if self.transposed {
+            self.grid.columns()
+        } else {
+            self.grid.rows()
+        }
+    }
+
+    pub fn columns(&self) -> usize {
+        if self.transposed {
+            self.grid.rows()
+        } else {
+            self.grid.columns()
+        }
+    }
+
+    /// Maps a coordinate in this view to the coordinate in the underlying grid
+    pub fn source_position(&self, row: usize, col: usize) -> Position {
+        let row = if self.flip_rows {
+            self.rows() - 1 - row
+        } else {
+            row
+        };
+        let col = if self.flip_columns {
+            self.columns() - 1 - col
+        } else {
+            col
+        };
+
+        if self.transposed {
+            Position { y: col, x: row }
+        } else {
+            Position { y: row, x: col }
+        }
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> u8 {
+        assert!(row < self.rows() && col < self.columns());
+        let Position { y, x } = self.source_position(row, col);
+        self.grid[y][x]
+    }
+
+    /// Rows become columns and columns become rows.
+    pub fn transpose(self) -> Self {
+        Self {
+            grid: self.grid,
+            transposed: !self.transposed,
+            flip_rows: self.flip_columns,
+            flip_columns: self.flip_rows,
+        }
+    }
+}
+
+#[allow(dead_code, reason = "day 13 only needs the transpose")]
+impl TransformedGridView<'_> {
+    /// Mirrors the grid along its vertical axis, i.e. reverses the order of columns
+    pub fn flip_horizontal(self) -> Self {
+        Self {
+            flip_columns: !self.flip_columns,
+            ..self
+        }
+    }
+
+    /// Mirrors the grid along its horizontal axis, i.e. reverses the order of rows
+    pub fn flip_vertical(self) -> Self {
+        Self {
+            flip_rows: !self.flip_rows,
+            ..self
+        }
+    }
+
+    /// Rotates by 90° clockwise: the first column (read bottom to top) becomes the first row.
+    pub fn rotate_cw(self) -> Self {
+        self.transpose().flip_horizontal()
+    }
+
+    /// Rotates by 90° counterclockwise: the last column (read top to bottom) becomes the first row.
+    pub fn rotate_ccw(self) -> Self {
+        self.transpose().flip_vertical()
+    }
+
+    pub fn rotate_180(self) -> Self {
+        self.flip_horizontal().flip_vertical()
+    }
+}
+
+impl Grid for TransformedGridView<'_> {
+    fn rows(&self) -> usize {
+        TransformedGridView::rows(self)
+    }
+
+    fn columns(&self) -> usize {
+        TransformedGridView::columns(self)
+    }
+
+    fn get(&self, row: usize, col: usize) -> u8 {
+        TransformedGridView::get(self, row, col)
+    }
+}
+
+/// A [`ByteGridView`] tiled infinitely in every direction, indexed by [`SignedPosition`]s.
+/// Every coordinate is valid, and wraps around modulo the size of the grid.
+#[derive(Debug, Clone, Copy)]
+#[allow(dead_code, reason = "no puzzle has had an infinite grid yet")]
+pub struct WrappingGridView<'a> {
+    grid: ByteGridView<'a>,
+}
+
+#[allow(dead_code, reason = "no puzzle has had an infinite grid yet")]
+impl<'a> WrappingGridView<'a> {
+    /// Maps a position to the corresponding position in the original grid
+    pub fn wrap(&self, pos: SignedPosition) -> Position {
+        Position {
+            y: pos.y.rem_euclid(self.grid.rows() as isize) as usize,
+            x: pos.x.rem_euclid(self.grid.columns() as isize) as usize,
+        }
+    }
+
+    /// Returns the copy of the grid that a position falls into.
+    /// The original grid is tile `(0, 0)`, the one to the left of it is `(0, -1)`, and so on.
+    pub fn tile(&self, pos: SignedPosition) -> SignedPosition {
+        SignedPosition {
+            y: pos.y.div_euclid(self.grid.rows() as isize),
+            x: pos.x.div_euclid(self.grid.columns() as isize),
+        }
+    }
+
+    /// Same as [`Grid::neighbors_by`], except that there is no bounds check since every neighbor exists
+    pub fn neighbors_by<O: Offset, I>(
+        self,
+        pos: SignedPosition,
+        offsets: I,
+    ) -> impl Iterator<Item = (u8, SignedPosition, O)> + 'a
+    where
+        I: IntoIterator<Item = O>,
+        I::IntoIter: 'a,
+    {
+        offsets.into_iter().map(move |offset| {
+            let neighbor = pos.offset(offset);
+            (self[neighbor], neighbor, offset)
+        })
+    }
+
+    pub fn orthogonal_neighbors_with_direction(
+        self,
+        pos: SignedPosition,
+    ) -> impl Iterator<Item = (u8, SignedPosition, Direction)> + 'a {
+        self.neighbors_by(pos, Direction::ALL)
+    }
+}
+
+impl<'a> Index<isize> for WrappingGridView<'a> {
+    type Output = [u8];
+
+    fn index(&self, index: isize) -> &Self::Output {
+        &self.grid[index.rem_euclid(self.grid.rows() as isize) as usize]
+    }
+}
+
+impl<'a> Index<SignedPosition> for WrappingGridView<'a> {
+    type Output = u8;
+
+    fn index(&self, pos: SignedPosition) -> &Self::Output {
+        let Position { y, x } = self.wrap(pos);
+        &self.grid[y][x]
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -240,4 +685,151 @@ mod tests {
         assert_eq!(&grid[1], b"...*.......");
         assert_eq!(&grid[9], b".664.598...");
     }
+
+    #[test]
+    fn test_transformed_views() {
+        // 123
+        // 456
+        let grid = ByteGridView::from("123\n456");
+        let collect = |view: TransformedGridView<'_>| {
+            (0..view.rows())
+                .map(|r| Grid::row(&view, r).collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(grid.column(1).collect::<Vec<_>>(), b"25");
+        assert_eq!(grid.column(2).rev().collect::<Vec<_>>(), b"63");
+        assert_eq!(collect(grid.view()), [b"123", b"456"]);
+        assert_eq!(collect(grid.transpose()), [b"14", b"25", b"36"]);
+        assert_eq!(collect(grid.rotate_cw()), [b"41", b"52", b"63"]);
+        assert_eq!(collect(grid.rotate_ccw()), [b"36", b"25", b"14"]);
+        assert_eq!(collect(grid.rotate_180()), [b"654", b"321"]);
+        assert_eq!(collect(grid.flip_horizontal()), [b"321", b"654"]);
+        assert_eq!(collect(grid.flip_vertical()), [b"456", b"123"]);
+        assert_eq!(
+            collect(grid.rotate_cw().rotate_cw()),
+            collect(grid.rotate_180())
+        );
+        assert_eq!(collect(grid.rotate_cw().rotate_ccw()), collect(grid.view()));
+        assert_eq!(collect(grid.transpose().transpose()), collect(grid.view()));
+        assert_eq!(
+            Grid::column(&grid.transpose(), 1).collect::<Vec<_>>(),
+            b"456"
+        );
+    }
+
+    #[test]
+    fn test_neighbors() {
+        // 123
+        // 456
+        // 789
+        let grid = ByteGridView::from("123\n456\n789");
+        let bytes =
+            |it: &mut dyn Iterator<Item = (u8, Position)>| it.map(|(c, _)| c).collect::<Vec<_>>();
+
+        assert_eq!(
+            bytes(&mut grid.all_neighbors(Position { y: 1, x: 1 })),
+            b"23698741"
+        );
+        assert_eq!(
+            bytes(&mut grid.all_neighbors(Position { y: 0, x: 0 })),
+            b"254"
+        );
+        assert_eq!(
+            bytes(&mut grid.all_neighbors(Position { y: 2, x: 2 })),
+            b"685"
+        );
+        assert_eq!(
+            grid.orthogonal_neighbors_with_direction(Position { y: 0, x: 1 })
+                .collect::<Vec<_>>(),
+            [
+                (b'3', Position { y: 0, x: 2 }, Direction::Right),
+                (b'5', Position { y: 1, x: 1 }, Direction::Down),
+                (b'1', Position { y: 0, x: 0 }, Direction::Left),
+            ]
+        );
+
+        // knight moves
+        let knight = [
+            (-2, -1),
+            (-2, 1),
+            (-1, -2),
+            (-1, 2),
+            (1, -2),
+            (1, 2),
+            (2, -1),
+            (2, 1),
+        ];
+        assert_eq!(
+            grid.neighbors_by(Position { y: 0, x: 0 }, knight)
+                .map(|(c, ..)| c)
+                .collect::<Vec<_>>(),
+            b"68"
+        );
+        assert_eq!(
+            grid.neighbors_by(Position { y: 1, x: 1 }, knight).count(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_wrapping() {
+        let grid = ByteGridView::from(
+            "...........
+.....###.#.
+.###.##..#.
+..#.#...#..
+....#.#....
+.##..S####.
+.##..#...#.
+.......##..
+.##.#.####.
+.##..##.##.
+...........",
+        )
+        .wrapping();
+
+        assert_eq!(grid[SignedPosition { y: -6, x: -6 }], b'S');
+        assert_eq!(grid[-1], grid[10]);
+        assert_eq!(
+            grid.wrap(SignedPosition { y: -1, x: 23 }),
+            Position { y: 10, x: 1 }
+        );
+        assert_eq!(
+            grid.tile(SignedPosition { y: -1, x: 23 }),
+            SignedPosition { y: -1, x: 2 }
+        );
+        assert_eq!(
+            grid.tile(SignedPosition { y: 10, x: 0 }),
+            SignedPosition { y: 0, x: 0 }
+        );
+
+        // Garden plots reachable in exactly n steps on the infinite map
+        let reachable = |n: usize| {
+            let mut seen = rustc_hash::FxHashSet::default();
+            let mut frontier = vec![SignedPosition { y: 5, x: 5 }];
+            let mut count = 0;
+            for step in 0..=n {
+                let mut next = Vec::new();
+                for pos in frontier {
+                    if !seen.insert(pos) {
+                        continue;
+                    }
+                    if step % 2 == n % 2 {
+                        count += 1;
+                    }
+                    next.extend(
+                        grid.orthogonal_neighbors_with_direction(pos)
+                            .filter(|&(c, ..)| c != b'#')
+                            .map(|(_, pos, _)| pos),
+                    );
+                }
+                frontier = next;
+            }
+            count
+        };
+        assert_eq!(reachable(6), 16);
+        assert_eq!(reachable(10), 50);
+        assert_eq!(reachable(50), 1594);
+    }
 }
@@ -1,16 +1,14 @@
-#![feature(
-    let_chains,
-    coroutines,
-    iter_from_coroutine,
-    iter_next_chunk,
-    lint_reasons
-)]
+#![feature(let_chains, coroutines, iter_from_coroutine, lint_reasons)]
 
+use std::env;
 use std::error::Error;
 use std::fs;
+use std::io;
+use std::io::IsTerminal;
 use std::time::Duration;
 use std::time::Instant;
 
+mod cycle;
 mod day1;
 mod day10;
 mod day11;
@@ -30,9 +28,70 @@ mod day6;
 mod day7;
 mod day8;
 mod day9;
+mod flood;
+mod geometry;
 mod grid;
+mod math;
+mod ranges;
+mod search;
+
+/// Whether to draw with ANSI colors, which only makes sense on a terminal
+fn use_color() -> bool {
+    io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none()
+}
+
+/// Draws the best routes through the day 17 city, instead of running the solutions
+fn show_path() -> Result<(), Box<dyn Error>> {
+    let input = fs::read_to_string("inputs/day17.txt")?;
+    let grid = grid::ByteGridView::from(input.trim_end());
+
+    for (part_n, runs) in [day17::CRUCIBLE, day17::ULTRA_CRUCIBLE]
+        .into_iter()
+        .enumerate()
+    {
+        let route = day17::best_route(grid, runs).ok_or("no route through the city")?;
+        println!("Day 17 Part {}: heat loss {}", part_n + 1, route.heat_loss);
+        println!("{}", day17::render(grid, &route, use_color()));
+    }
+
+    Ok(())
+}
+
+/// Draws where the day 16 part 1 beam goes and reports any loops it runs into, instead of running the
+/// solutions
+fn trace_beams() -> Result<(), Box<dyn Error>> {
+    let input = fs::read_to_string("inputs/day16.txt")?;
+    let grid = grid::ByteGridView::from(input.trim_end());
+    let trace = day16::trace_beam(grid, grid::Position { y: 0, x: 0 }, grid::Direction::Right);
+
+    println!("{}", day16::render_beams(grid, &trace));
+    println!("{}", day16::render_energized(&trace));
+    println!("Energized tiles: {}", trace.energized().count());
+    for (pos, dir) in trace.loops() {
+        println!("Beam loops back to {pos:?} going {dir:?}");
+    }
+
+    Ok(())
+}
+
+/// Draws the day 10 pipe maze with the loop and the tiles it encloses, instead of running the solutions
+fn show_loop() -> Result<(), Box<dyn Error>> {
+    let input = fs::read_to_string("inputs/day10.txt")?;
+    let grid = grid::ByteGridView::from(input.trim_end());
+
+    println!("{}", day10::render(grid, use_color()));
+
+    Ok(())
+}
 
 fn main() -> Result<(), Box<dyn Error>> {
+    match env::args().nth(1).as_deref() {
+        Some("--show-path") => return show_path(),
+        Some("--trace-beams") => return trace_beams(),
+        Some("--show-loop") => return show_loop(),
+        _ => {}
+    }
+
     let days = [
         [day1::part1, day1::part2],
         [day2::part1, day2::part2],
@@ -45,13 +104,13 @@ fn main() -> Result<(), Box<dyn Error>> {
         [day9::part1, day9::part2],
         [day10::part1, day10::part2],
         [day11::part1, day11::part2],
-        [day12::part1, |_| 0 /* todo */],
+        [day12::part1, day12::part2],
         [day13::part1, day13::part2],
-        [day14::part1, |_| 0 /* todo */],
+        [day14::part1, day14::part2],
         [day15::part1, day15::part2],
         [day16::part1, day16::part2],
         [day17::part1, day17::part2],
-        [day18::part1, |_| 0 /* todo */],
+        [day18::part1, day18::part2],
         [day19::part1, day19::part2],
     ];
 
@@ -0,0 +1,155 @@
+//! Number theory helpers.
+//!
+//! Everything takes and returns `i64`, but products are computed as `i128` so that nothing
+//! overflows as long as the moduli (and results) fit in an `i64`.
+
+pub fn gcd(a: i64, b: i64) -> i64 {
+    if b == 0 {
+        a.abs()
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+pub fn lcm(a: i64, b: i64) -> i64 {
+    if a == 0 || b == 0 {
+        0
+    } else {
+        (a / gcd(a, b) * b).abs()
+    }
+}
+
+/// Integer square root, i.e. the largest `x` such that `x * x <= n`
+pub fn isqrt(n: i64) -> i64 {
+    assert!(n >= 0, "square root of negative number {n}");
+    // The float square root is at most off by one for numbers this large, so only fix that up
+    let mut x = (n as f64).sqrt() as i64;
+    while !x.checked_mul(x).is_some_and(|sq| sq <= n) {
+        x -= 1;
+    }
+    while (x + 1).checked_mul(x + 1).is_some_and(|sq| sq <= n) {
+        x += 1;
+    }
+    x
+}
+
+/// Extended Euclidean algorithm, returns `(gcd, x, y)` such that `a * x + b * y = gcd`
+pub fn ext_gcd(a: i64, b: i64) -> (i64, i64, i64) {
+    if b == 0 {
+        (a, 1, 0)
+    } else {
+        let (g, x, y) = ext_gcd(b, a % b);
+        (g, y, x - (a / b) * y)
+    }
+}
+
+/// Returns `x` in `0..m` such that `a * x = 1 (mod m)`, if there is one (i.e. if `a` and `m` are coprime)
+#[allow(dead_code, reason = "`crt_pair` works with `ext_gcd` directly")]
+pub fn mod_inverse(a: i64, m: i64) -> Option<i64> {
+    let (g, x, _) = ext_gcd(a.rem_euclid(m), m);
+    (g == 1).then(|| x.rem_euclid(m))
+}
+
+/// Returns `a * b mod m`, in `0..m`
+#[allow(dead_code, reason = "only used by `mod_pow`")]
+pub fn mod_mul(a: i64, b: i64, m: i64) -> i64 {
+    (a as i128 * b as i128).rem_euclid(m as i128) as i64
+}
+
+/// Returns `base ^ exp mod m`, in `0..m`, using exponentiation by squaring
+#[allow(dead_code, reason = "no puzzle has needed modular exponentiation yet")]
+pub fn mod_pow(base: i64, mut exp: u64, m: i64) -> i64 {
+    let mut result = 1 % m;
+    let mut base = base.rem_euclid(m);
+
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = mod_mul(result, base, m);
+        }
+        base = mod_mul(base, base, m);
+        exp >>= 1;
+    }
+
+    result
+}
+
+/// Combines `x = a1 (mod m1)` and `x = a2 (mod m2)` into a single congruence `x = a (mod lcm(m1, m2))`
+/// and returns `(a, lcm(m1, m2))`. The moduli don't need to be coprime, but if they aren't, there
+/// might not be a solution.
+pub fn crt_pair(a1: i64, m1: i64, a2: i64, m2: i64) -> Option<(i64, i64)> {
+    let (g, p, _) = ext_gcd(m1, m2);
+    if (a2 - a1) % g != 0 {
+        return None;
+    }
+
+    let m2g = (m2 / g) as i128;
+    let lcm = m1 as i128 * m2g;
+    let k = ((a2 - a1) as i128 / g as i128 * p as i128).rem_euclid(m2g);
+    let a = (a1 as i128 + m1 as i128 * k).rem_euclid(lcm);
+
+    Some((
+        a as i64,
+        i64::try_from(lcm).expect("combined modulus overflows an i64"),
+    ))
+}
+
+/// Generalized Chinese remainder theorem: solves the system of congruences `x = a (mod m)` for all
+/// given `(a, m)` pairs, returning the solution as `(x, lcm of all moduli)`, or `None` if there is none.
+pub fn crt(congruences: impl IntoIterator<Item = (i64, i64)>) -> Option<(i64, i64)> {
+    congruences
+        .into_iter()
+        .try_fold((0, 1), |(a, m), (a2, m2)| crt_pair(a, m, a2, m2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_math() {
+        assert_eq!(gcd(12, 18), 6);
+        assert_eq!(gcd(-12, 18), 6);
+        assert_eq!(gcd(0, 7), 7);
+        assert_eq!(lcm(4, 6), 12);
+        assert_eq!(lcm(0, 6), 0);
+
+        assert_eq!(isqrt(0), 0);
+        assert_eq!(isqrt(15), 3);
+        assert_eq!(isqrt(16), 4);
+        assert_eq!(isqrt(3_037_000_499 * 3_037_000_499), 3_037_000_499);
+        assert_eq!(isqrt(3_037_000_499 * 3_037_000_499 - 1), 3_037_000_498);
+        assert_eq!(isqrt(i64::MAX), 3_037_000_499);
+
+        for (a, b) in [(240, 46), (17, 5), (-35, 15), (1 << 40, 3 << 20)] {
+            let (g, x, y) = ext_gcd(a, b);
+            assert_eq!(g.abs(), gcd(a, b));
+            assert_eq!(a * x + b * y, g);
+        }
+
+        assert_eq!(mod_inverse(3, 11), Some(4));
+        assert_eq!(mod_inverse(-3, 11), Some(7));
+        assert_eq!(mod_inverse(2, 4), None);
+
+        const BIG: i64 = 999_999_999_999_999_989;
+        assert_eq!(mod_mul(BIG - 1, BIG - 1, BIG), 1);
+        assert_eq!(mod_pow(2, 10, 1_000_000_007), 1024);
+        assert_eq!(mod_pow(5, 0, 1), 0);
+        // Fermat's little theorem
+        assert_eq!(mod_pow(123_456_789, BIG as u64 - 1, BIG), 1);
+
+        assert_eq!(crt([(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        // Non-coprime moduli
+        assert_eq!(crt_pair(2, 6, 8, 10), Some((8, 30)));
+        assert_eq!(crt_pair(1, 4, 2, 6), None);
+        assert_eq!(
+            crt([(0, 263 * 43), (0, 263 * 47)]),
+            Some((0, 263 * 43 * 47))
+        );
+        // Intermediate products that don't fit into an i64
+        let (m1, m2) = (2_999_999_929, 3_000_000_019);
+        assert_eq!(gcd(m1, m2), 1);
+        let (x, m) = crt([(m1 - 1, m1), (m2 - 2, m2)]).unwrap();
+        assert_eq!(m, m1 * m2);
+        assert_eq!((x % m1, x % m2), (m1 - 1, m2 - 2));
+    }
+}
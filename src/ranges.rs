@@ -0,0 +1,499 @@
+//! Sets of integers stored as ranges, for when there are far too many values to handle one by one.
+//!
+//! Ranges are half-open (`start..end`) like in the standard library, but unlike [`std::ops::Range`]
+//! [`Interval`] is `Copy`. Inclusive ranges from puzzle inputs can be converted with `From`.
+
+use std::ops::Range;
+use std::ops::RangeInclusive;
+
+use itertools::Itertools;
+
+/// The integers `start..end`. Empty if `start >= end`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Interval {
+    pub start: i64,
+    pub end: i64,
+}
+
+impl Interval {
+    pub fn new(start: i64, end: i64) -> Self {
+        Self { start, end }
+    }
+
+    /// Returns the number of integers in this interval
+    pub fn len(self) -> i64 {
+        (self.end - self.start).max(0)
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.start >= self.end
+    }
+
+    pub fn contains(self, value: i64) -> bool {
+        (self.start..self.end).contains(&value)
+    }
+
+    /// Returns the values that are in both intervals (which might be an empty interval)
+    pub fn intersection(self, other: Interval) -> Interval {
+        Interval::new(self.start.max(other.start), self.end.min(other.end))
+    }
+
+    /// Splits this interval into the values below `cut` and the values at or above it.
+    /// Either of them can be empty.
+    pub fn split_at(self, cut: i64) -> (Interval, Interval) {
+        let cut = cut.clamp(self.start, self.end.max(self.start));
+        (
+            Interval::new(self.start, cut),
+            Interval::new(cut, self.end.max(cut)),
+        )
+    }
+
+    /// Adds `offset` to every value in this interval
+    pub fn shift(self, offset: i64) -> Interval {
+        Interval::new(self.start + offset, self.end + offset)
+    }
+}
+
+impl From<Range<i64>> for Interval {
+    fn from(range: Range<i64>) -> Self {
+        Interval::new(range.start, range.end)
+    }
+}
+
+impl From<RangeInclusive<i64>> for Interval {
+    fn from(range: RangeInclusive<i64>) -> Self {
+        Interval::new(*range.start(), *range.end() + 1)
+    }
+}
+
+/// A set of integers, stored as sorted intervals that neither overlap nor touch
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct RangeSet {
+    intervals: Vec<Interval>,
+}
+
+impl RangeSet {
+    #[allow(dead_code, reason = "sets are always collected from intervals so far")]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the intervals making up this set, in ascending order
+    #[allow(dead_code, reason = "only used by tests")]
+    pub fn intervals(&self) -> &[Interval] {
+        &self.intervals
+    }
+
+    /// Returns the number of integers in this set
+    #[allow(dead_code, reason = "day 5 only needs the smallest value")]
+    pub fn len(&self) -> i64 {
+        self.intervals.iter().map(|interval| interval.len()).sum()
+    }
+
+    #[allow(dead_code, reason = "day 5 only needs the smallest value")]
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    #[allow(dead_code, reason = "only used by tests")]
+    pub fn contains(&self, value: i64) -> bool {
+        let index = self
+            .intervals
+            .partition_point(|interval| interval.end <= value);
+        self.intervals
+            .get(index)
+            .is_some_and(|interval| interval.contains(value))
+    }
+
+    /// Returns the smallest value in this set
+    pub fn min(&self) -> Option<i64> {
+        self.intervals.first().map(|interval| interval.start)
+    }
+
+    /// Returns the largest value in this set
+    #[allow(dead_code, reason = "day 5 only needs the smallest value")]
+    pub fn max(&self) -> Option<i64> {
+        self.intervals.last().map(|interval| interval.end - 1)
+    }
+
+    #[allow(dead_code, reason = "only used by tests")]
+    pub fn union(&self, other: &RangeSet) -> RangeSet {
+        self.intervals
+            .iter()
+            .chain(&other.intervals)
+            .copied()
+            .collect()
+    }
+
+    #[allow(dead_code, reason = "only used by tests")]
+    pub fn intersection(&self, other: &RangeSet) -> RangeSet {
+        let mut intervals = Vec::new();
+        let (mut i, mut j) = (0, 0);
+
+        while let (Some(&a), Some(&b)) = (self.intervals.get(i), other.intervals.get(j)) {
+            let both = a.intersection(b);
+            if !both.is_empty() {
+                intervals.push(both);
+            }
+
+            // Whichever one ends first can't overlap with anything else from the other set
+            if a.end < b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+
+        RangeSet { intervals }
+    }
+
+    /// Returns the values in this set that aren't in `other`
+    pub fn difference(&self, other: &RangeSet) -> RangeSet {
+        let mut intervals = Vec::new();
+        let mut others = other.intervals.iter().peekable();
+
+        for &interval in &self.intervals {
+            while others.next_if(|o| o.end <= interval.start).is_some() {}
+
+            // What's left of the interval starts at `start`, and the intervals cut out of it are sorted,
+            // so every gap between two of them is part of the result
+            let mut start = interval.start;
+            let mut cuts = others.clone();
+            while let Some(cut) = cuts.next_if(|o| o.start < interval.end) {
+                if cut.start > start {
+                    intervals.push(Interval::new(start, cut.start));
+                }
+                start = start.max(cut.end);
+            }
+
+            if start < interval.end {
+                intervals.push(Interval::new(start, interval.end));
+            }
+        }
+
+        RangeSet { intervals }
+    }
+
+    /// Splits this set into the values below `cut` and the values at or above it
+    #[allow(dead_code, reason = "day 19 splits single intervals instead")]
+    pub fn split_at(&self, cut: i64) -> (RangeSet, RangeSet) {
+        let (below, above) = self
+            .intervals
+            .iter()
+            .map(|interval| interval.split_at(cut))
+            .unzip::<_, _, Vec<_>, Vec<_>>();
+
+        (below.into_iter().collect(), above.into_iter().collect())
+    }
+
+    /// Maps every value with a piecewise-linear function that adds `offset` to the values in `domain`
+    /// for every `(domain, offset)` piece, and leaves values outside of all domains unchanged.
+    /// The domains must not overlap.
+    pub fn map_piecewise(&self, pieces: impl IntoIterator<Item = (Interval, i64)>) -> RangeSet {
+        let pieces = pieces.into_iter().collect::<Vec<_>>();
+        let domains = pieces
+            .iter()
+            .map(|&(domain, _)| domain)
+            .collect::<RangeSet>();
+
+        let mapped = pieces.iter().flat_map(|&(domain, offset)| {
+            self.intervals
+                .iter()
+                .map(move |interval| interval.intersection(domain).shift(offset))
+        });
+
+        self.difference(&domains)
+            .intervals
+            .into_iter()
+            .chain(mapped)
+            .collect()
+    }
+}
+
+impl FromIterator<Interval> for RangeSet {
+    fn from_iter<T: IntoIterator<Item = Interval>>(iter: T) -> Self {
+        let mut sorted = iter
+            .into_iter()
+            .filter(|interval| !interval.is_empty())
+            .collect::<Vec<_>>();
+        sorted.sort_unstable();
+
+        let mut intervals = Vec::<Interval>::with_capacity(sorted.len());
+        for interval in sorted {
+            match intervals.last_mut() {
+                Some(last) if interval.start <= last.end => last.end = last.end.max(interval.end),
+                _ => intervals.push(interval),
+            }
+        }
+
+        RangeSet { intervals }
+    }
+}
+
+impl From<Interval> for RangeSet {
+    fn from(interval: Interval) -> Self {
+        [interval].into_iter().collect()
+    }
+}
+
+/// A function on integers that adds a fixed offset to all values in one of a number of disjoint intervals
+/// (the pieces), and leaves values outside of all pieces unchanged
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct PiecewiseMap {
+    /// `(domain, offset)`, sorted by domain. Domains never overlap or are empty, and offsets are never 0.
+    pieces: Vec<(Interval, i64)>,
+}
+
+impl PiecewiseMap {
+    /// The identity function
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn apply(&self, value: i64) -> i64 {
+        let index = self
+            .pieces
+            .partition_point(|(domain, _)| domain.end <= value);
+        match self.pieces.get(index) {
+            Some(&(domain, offset)) if domain.contains(value) => value + offset,
+            _ => value,
+        }
+    }
+
+    /// Returns the image of all values in a set
+    pub fn apply_ranges(&self, values: &RangeSet) -> RangeSet {
+        values.map_piecewise(self.pieces.iter().copied())
+    }
+
+    /// Returns the smallest value that any of the values in the set map to
+    pub fn min_over(&self, values: &RangeSet) -> Option<i64> {
+        self.apply_ranges(values).min()
+    }
+
+    /// Returns all values that map to one of the values in the set
+    #[allow(dead_code, reason = "only used by tests")]
+    pub fn preimage(&self, values: &RangeSet) -> RangeSet {
+        self.segments()
+            .flat_map(|(domain, offset)| {
+                values
+                    .intersection(&RangeSet::from(domain.shift(offset)))
+                    .intervals
+                    .into_iter()
+                    .map(move |image| image.shift(-offset))
+            })
+            .collect()
+    }
+
+    /// Returns the map that first applies this map and then `next`
+    pub fn then(&self, next: &PiecewiseMap) -> PiecewiseMap {
+        self.segments()
+            .flat_map(|(domain, offset)| {
+                // Every value in `domain` ends up in `image`, and then gets moved by whichever piece of
+                // `next` it lands in
+                let image = domain.shift(offset);
+                next.segments().map(move |(next_domain, next_offset)| {
+                    let both = image.intersection(next_domain);
+                    (both.shift(-offset), offset + next_offset)
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the inverse function, or `None` if this map isn't a bijection
+    /// (i.e. if two pieces overlap after being moved)
+    #[allow(dead_code, reason = "only used by tests")]
+    pub fn inverse(&self) -> Option<PiecewiseMap> {
+        let mut images = self
+            .pieces
+            .iter()
+            .map(|&(domain, offset)| domain.shift(offset))
+            .chain(
+                self.segments()
+                    .filter(|&(_, offset)| offset == 0)
+                    .map(|(domain, _)| domain),
+            )
+            .collect::<Vec<_>>();
+        images.sort_unstable();
+
+        // The domains cover every value exactly once, so if the images don't overlap, they cover every value
+        // exactly once, too
+        if images.windows(2).any(|w| w[0].end > w[1].start) {
+            return None;
+        }
+
+        Some(
+            self.pieces
+                .iter()
+                .map(|&(domain, offset)| (domain.shift(offset), -offset))
+                .collect(),
+        )
+    }
+
+    /// Returns the pieces together with the gaps between them (with an offset of 0), covering all values
+    fn segments(&self) -> impl Iterator<Item = (Interval, i64)> + '_ {
+        let starts = self.pieces.iter().map(|(domain, _)| domain.end);
+        let gaps = [i64::MIN]
+            .into_iter()
+            .chain(starts)
+            .zip(
+                self.pieces
+                    .iter()
+                    .map(|(domain, _)| domain.start)
+                    .chain([i64::MAX]),
+            )
+            .map(|(start, end)| (Interval::new(start, end), 0));
+
+        gaps.interleave(self.pieces.iter().copied())
+            .filter(|(domain, _)| !domain.is_empty())
+    }
+}
+
+impl FromIterator<(Interval, i64)> for PiecewiseMap {
+    /// Collects `(domain, offset)` pieces into a map. The domains must not overlap.
+    fn from_iter<T: IntoIterator<Item = (Interval, i64)>>(iter: T) -> Self {
+        let mut pieces = iter
+            .into_iter()
+            .filter(|&(domain, offset)| !domain.is_empty() && offset != 0)
+            .collect::<Vec<_>>();
+        pieces.sort_unstable();
+        assert!(
+            pieces.windows(2).all(|w| w[0].0.end <= w[1].0.start),
+            "pieces of a map must not overlap"
+        );
+
+        // Merge neighboring pieces that do the same thing, so that equal functions compare equal
+        let mut merged = Vec::<(Interval, i64)>::with_capacity(pieces.len());
+        for (domain, offset) in pieces {
+            match merged.last_mut() {
+                Some((last, last_offset)) if last.end == domain.start && *last_offset == offset => {
+                    last.end = domain.end
+                }
+                _ => merged.push((domain, offset)),
+            }
+        }
+
+        PiecewiseMap { pieces: merged }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ranges() {
+        let interval = Interval::from(3..=7);
+        assert_eq!(interval, Interval::new(3, 8));
+        assert_eq!(interval.len(), 5);
+        assert_eq!(
+            interval.split_at(5),
+            (Interval::new(3, 5), Interval::new(5, 8))
+        );
+        assert!(interval.split_at(0).0.is_empty());
+        assert!(interval.split_at(10).1.is_empty());
+        assert!(interval.intersection(Interval::new(8, 10)).is_empty());
+
+        let set = [0..3, 10..12, 2..5, 5..6, 20..20]
+            .into_iter()
+            .map(Interval::from)
+            .collect::<RangeSet>();
+        assert_eq!(
+            set.intervals(),
+            [Interval::new(0, 6), Interval::new(10, 12)]
+        );
+        assert_eq!(set.len(), 8);
+        assert_eq!((set.min(), set.max()), (Some(0), Some(11)));
+        assert!(set.contains(11) && !set.contains(6) && !set.contains(-1));
+
+        // Everything below 6 moves up by 100, 10 moves down to 6, which then touches 5
+        let mapped = set.map_piecewise([(Interval::new(3, 6), 100), (Interval::new(10, 11), -4)]);
+        assert_eq!(
+            mapped.intervals(),
+            [
+                Interval::new(0, 3),
+                Interval::new(6, 7),
+                Interval::new(11, 12),
+                Interval::new(103, 106)
+            ]
+        );
+
+        // Compare the set operations with a brute force over all subsets of 0..8
+        let to_set = |mask: u32| {
+            (0..8)
+                .filter(|bit| mask & 1 << bit != 0)
+                .map(|value| Interval::new(value, value + 1))
+                .collect::<RangeSet>()
+        };
+        let to_mask = |set: &RangeSet| {
+            (0..8)
+                .filter(|&value| set.contains(value))
+                .fold(0, |mask, bit| mask | 1 << bit)
+        };
+        for a in 0..256 {
+            let set_a = to_set(a);
+            assert_eq!(to_mask(&set_a), a);
+            assert_eq!(set_a.len(), a.count_ones() as i64);
+
+            let (below, above) = set_a.split_at(3);
+            assert_eq!((to_mask(&below), to_mask(&above)), (a & 0b111, a & !0b111));
+
+            for b in 0..256 {
+                let set_b = to_set(b);
+                assert_eq!(to_mask(&set_a.union(&set_b)), a | b);
+                assert_eq!(to_mask(&set_a.intersection(&set_b)), a & b);
+                assert_eq!(to_mask(&set_a.difference(&set_b)), a & !b);
+                // Results have to be normalized, too, so equal sets compare equal
+                assert_eq!(set_a.difference(&set_b), to_set(a & !b));
+            }
+        }
+
+        // Swaps 0..3 and 3..5, and moves 10..12 to 20..22
+        let swap = [(0..3, 2), (3..5, -3), (10..12, 10)]
+            .into_iter()
+            .map(|(domain, offset)| (Interval::from(domain), offset))
+            .collect::<PiecewiseMap>();
+        let values = (-5..30).collect::<Vec<_>>();
+        assert_eq!(
+            values
+                .iter()
+                .map(|&v| swap.apply(v))
+                .take(17)
+                .collect::<Vec<_>>(),
+            [-5, -4, -3, -2, -1, 2, 3, 4, 0, 1, 5, 6, 7, 8, 9, 20, 21]
+        );
+        assert_eq!(swap.min_over(&set), Some(0));
+        assert_eq!(swap.min_over(&Interval::new(1, 4).into()), Some(0));
+        assert_eq!(swap.min_over(&RangeSet::new()), None);
+        assert_eq!(
+            swap.apply_ranges(&Interval::new(1, 4).into()).intervals(),
+            [Interval::new(0, 1), Interval::new(3, 5)]
+        );
+
+        // Moving 10..12 away means that 20..22 is hit twice and 10..12 not at all
+        assert_eq!(swap.inverse(), None);
+        assert_eq!(
+            swap.preimage(&Interval::new(20, 21).into()).intervals(),
+            [Interval::new(10, 11), Interval::new(20, 21)]
+        );
+        assert!(swap.preimage(&Interval::new(10, 12).into()).is_empty());
+
+        let shift = [(Interval::new(0, 5), 7), (Interval::new(7, 12), -7)]
+            .into_iter()
+            .collect::<PiecewiseMap>();
+        let composed = swap.then(&shift);
+        for &v in &values {
+            assert_eq!(composed.apply(v), shift.apply(swap.apply(v)));
+        }
+
+        let inverse = shift.inverse().unwrap();
+        assert_eq!(shift.then(&inverse), PiecewiseMap::new());
+        assert_eq!(inverse.then(&shift), PiecewiseMap::new());
+        for &v in &values {
+            assert_eq!(inverse.apply(shift.apply(v)), v);
+            assert_eq!(
+                shift.preimage(&Interval::new(v, v + 1).into()),
+                Interval::new(inverse.apply(v), inverse.apply(v) + 1).into()
+            );
+        }
+    }
+}
@@ -0,0 +1,70 @@
+//! Generic graph searches.
+//!
+//! None of these need an explicit graph. Instead they take the start state(s) and a `successors`
+//! function returning the states reachable in one step, so any `Clone + Eq + Hash` type can be used as
+//! a state.
+
+use std::hash::Hash;
+
+use rustc_hash::FxHashSet;
+
+/// Returns every state reachable from the start states (including the start states themselves)
+#[allow(dead_code, reason = "only used by the flood fill so far")]
+pub fn reachable<S, I>(
+    starts: impl IntoIterator<Item = S>,
+    mut successors: impl FnMut(&S) -> I,
+) -> FxHashSet<S>
+where
+    S: Clone + Eq + Hash,
+    I: IntoIterator<Item = S>,
+{
+    let mut seen = FxHashSet::default();
+    let mut stack = Vec::new();
+
+    for start in starts {
+        if seen.insert(start.clone()) {
+            stack.push(start);
+        }
+    }
+
+    while let Some(state) = stack.pop() {
+        for next in successors(&state) {
+            if seen.insert(next.clone()) {
+                stack.push(next);
+            }
+        }
+    }
+
+    seen
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::ByteGridView;
+    use crate::grid::Grid;
+    use crate::grid::Position;
+
+    #[test]
+    fn test_reachable() {
+        let grid = ByteGridView::from(
+            "S.#...
+.##.#.
+...1#.
+##.#..
+....#E",
+        );
+        let start = Position { y: 0, x: 0 };
+        let neighbors = |&pos: &Position| {
+            grid.orthogonal_neighbors_with_direction(pos)
+                .filter(|&(c, ..)| c != b'#')
+                .map(|(_, pos, _)| pos)
+                .collect::<Vec<_>>()
+        };
+
+        let seen = reachable([start], neighbors);
+        assert_eq!(seen.len(), 21);
+        assert!(seen.contains(&Position { y: 4, x: 5 }));
+        assert!(!seen.contains(&Position { y: 0, x: 2 }));
+    }
+}